@@ -12,7 +12,7 @@ fn main() {
 
     // Try to get git hash
     let git_hash = Command::new("git")
-        .args(&["rev-parse", "--short", "HEAD"])
+        .args(["rev-parse", "--short", "HEAD"])
         .output()
         .map(|output| String::from_utf8(output.stdout).unwrap_or_default().trim().to_string())
         .unwrap_or_else(|e| {
@@ -51,7 +51,7 @@ fn main() {
     let options = FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
 
     // List of binaries to include
-    let binaries = vec!["laracli.exe", "laracli-service.exe", "laracli-service-config.exe"];
+    let binaries = vec!["laracli.exe", "laracli-service.exe"];
 
     // Add each binary to the zip
     for binary in binaries {
@@ -0,0 +1,97 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::api::Laracli;
+use crate::error::LaracliError;
+use crate::helpers::mysql::{MYSQL_HOST, MYSQL_PORT, MYSQL_USER};
+use crate::helpers::path;
+use crate::helpers::process::DevProcess;
+
+// Schemas MySQL creates for itself
+const SYSTEM_DATABASES: [&str; 4] = ["information_schema", "mysql", "performance_schema", "sys"];
+
+/// Databases of the bundled MySQL server, which has to be running
+pub struct Databases<'a> {
+    laracli: &'a Laracli,
+}
+
+impl<'a> Databases<'a> {
+    pub(crate) fn new(laracli: &'a Laracli) -> Databases<'a> {
+        Databases { laracli }
+    }
+
+    /// Databases of the projects, MySQL's own schemas left out
+    pub fn list(&self) -> Result<Vec<String>, LaracliError> {
+        let output = self.laracli.scoped(|| run_sql("SHOW DATABASES"))?;
+        Ok(output
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|name| !name.is_empty() && !SYSTEM_DATABASES.contains(&name.as_str()))
+            .collect())
+    }
+
+    pub fn create(&self, name: &str) -> Result<(), LaracliError> {
+        validate_name(name)?;
+        self.laracli.scoped(|| {
+            run_sql(&format!(
+                "CREATE DATABASE IF NOT EXISTS `{}` CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci",
+                name
+            ))
+        })?;
+        Ok(())
+    }
+
+    pub fn drop(&self, name: &str) -> Result<(), LaracliError> {
+        validate_name(name)?;
+        if SYSTEM_DATABASES.contains(&name.to_lowercase().as_str()) {
+            return Err(LaracliError::InvalidArgument(format!("{} belongs to MySQL itself", name)));
+        }
+        self.laracli.scoped(|| run_sql(&format!("DROP DATABASE IF EXISTS `{}`", name)))?;
+        Ok(())
+    }
+}
+
+// Letters, digits, `_` and `$` need no escaping inside backticks
+fn validate_name(name: &str) -> Result<(), LaracliError> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if !valid {
+        return Err(LaracliError::InvalidArgument(format!("Invalid database name: {}", name)));
+    }
+    Ok(())
+}
+
+// Run one statement with the mysql client, returns its tab separated output without headers
+fn run_sql(sql: &str) -> Result<String, LaracliError> {
+    if !DevProcess::Mysql.is_running() {
+        return Err(LaracliError::NotRunning { service: "MySQL" });
+    }
+    let mysql_path = path::get_mysql_path()?;
+    let client = path::require_tool("MySQL", Path::new(&mysql_path).join("bin").join("mysql.exe"))?;
+
+    let output = Command::new(client)
+        .arg(format!("--host={}", MYSQL_HOST))
+        .arg(format!("--port={}", MYSQL_PORT))
+        .arg(format!("--user={}", MYSQL_USER))
+        .args(["--batch", "--skip-column-names", "--execute", sql])
+        .output()?;
+    if !output.status.success() {
+        return Err(LaracliError::Other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_database_names() {
+        assert!(validate_name("blog_dev").is_ok());
+        assert!(validate_name("blog`; DROP DATABASE mysql").is_err());
+        assert!(validate_name("").is_err());
+    }
+}
@@ -0,0 +1,88 @@
+//! Library API for tools built on laracli, like editor extensions. Handles
+//! return data and never print, failures are [`LaracliError`]s. Each handle
+//! keeps its own laracli directory, nothing process-wide is changed. The
+//! laracli service only applies changes for the directory it runs from,
+//! handles on another directory update hosts and Nginx themselves.
+//!
+//! ```no_run
+//! use laracli::api::{Laracli, LinkOptions};
+//!
+//! let laracli = Laracli::open(r"C:\laracli")?;
+//! let site = laracli.sites().link(r"C:\www\blog", &LinkOptions::default())?;
+//! println!("{} is served from {:?}", site.url, site.document_root);
+//! for service in laracli.services().list() {
+//!     println!("{} running: {}", service.name, service.running);
+//! }
+//! # Ok::<(), laracli::error::LaracliError>(())
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use crate::error::LaracliError;
+use crate::helpers::config::{self, Config};
+use crate::helpers::{output, path};
+
+mod databases;
+mod php;
+mod services;
+mod sites;
+
+pub use databases::Databases;
+pub use php::{Php, PhpExtension};
+pub use services::{ServiceStatus, Services};
+pub use crate::helpers::linking::LinkOptions;
+pub use sites::{Site, SiteSource, Sites};
+
+/// An opened laracli installation, the entry point of the API
+#[derive(Debug, Clone)]
+pub struct Laracli {
+    home: PathBuf,
+}
+
+impl Laracli {
+    /// Open the laracli directory `home`, holding tools/ and config.json
+    pub fn open(home: impl AsRef<Path>) -> Result<Laracli, LaracliError> {
+        let home = home.as_ref().to_path_buf();
+        if !home.is_dir() {
+            return Err(LaracliError::NotFound(format!(
+                "{} is not a laracli directory",
+                home.display()
+            )));
+        }
+        let laracli = Laracli { home };
+        // Fail early on a broken config.json
+        laracli.config()?;
+        Ok(laracli)
+    }
+
+    // Run a call with helpers resolving paths from this handle's home. They report
+    // progress with `say!`, callers get return values instead.
+    pub(crate) fn scoped<T>(&self, f: impl FnOnce() -> T) -> T {
+        path::with_home(&self.home, || output::quietly(f))
+    }
+
+    pub fn home(&self) -> &Path {
+        &self.home
+    }
+
+    /// config.json as it is now, re-read on every call
+    pub fn config(&self) -> Result<Config, LaracliError> {
+        self.scoped(config::read_config)
+    }
+
+    pub fn sites(&self) -> Sites<'_> {
+        Sites::new(self)
+    }
+
+    pub fn services(&self) -> Services<'_> {
+        Services::new(self)
+    }
+
+    pub fn php(&self) -> Php<'_> {
+        Php::new(self)
+    }
+
+    pub fn databases(&self) -> Databases<'_> {
+        Databases::new(self)
+    }
+}
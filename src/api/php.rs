@@ -0,0 +1,75 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::api::Laracli;
+use crate::error::LaracliError;
+use crate::helpers::{path, php_ini};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PhpExtension {
+    pub name: String,
+    pub enabled: bool,
+    /// Its DLL is present in ext/
+    pub installed: bool,
+}
+
+/// The bundled PHP and its php.ini
+pub struct Php<'a> {
+    laracli: &'a Laracli,
+}
+
+impl<'a> Php<'a> {
+    pub(crate) fn new(laracli: &'a Laracli) -> Php<'a> {
+        Php { laracli }
+    }
+
+    /// Directory of the bundled PHP
+    pub fn path(&self) -> Result<PathBuf, LaracliError> {
+        self.laracli.scoped(|| path::require_tool("PHP", path::get_php_path()?))
+    }
+
+    fn ini_path(&self) -> Result<PathBuf, LaracliError> {
+        path::require_tool("PHP", self.path()?.join("php.ini"))
+    }
+
+    /// Extensions listed in php.ini
+    pub fn extensions(&self) -> Result<Vec<PhpExtension>, LaracliError> {
+        let ext_dir = self.path()?.join("ext");
+        let contents = fs::read_to_string(self.ini_path()?)?;
+        Ok(php_ini::extensions(&contents)
+            .into_iter()
+            .map(|(name, enabled)| PhpExtension {
+                installed: ext_dir.join(format!("php_{}.dll", name)).exists(),
+                name,
+                enabled,
+            })
+            .collect())
+    }
+
+    /// Enable or disable an extension in php.ini, returns whether the file changed.
+    /// PHP picks the change up once restarted with `services().restart("php")`.
+    pub fn set_extension(&self, extension: &str, enabled: bool) -> Result<bool, LaracliError> {
+        let ini_path = self.ini_path()?;
+        let contents = fs::read_to_string(&ini_path)?;
+        let edit = if enabled {
+            let dll = self.path()?.join("ext").join(format!("php_{}.dll", extension));
+            if !dll.exists() {
+                return Err(LaracliError::NotFound(format!(
+                    "The {} extension is not installed, {} was not found",
+                    extension,
+                    dll.display()
+                )));
+            }
+            php_ini::enable_extension(&contents, extension)
+        } else {
+            php_ini::disable_extension(&contents, extension)
+        };
+
+        if edit.changed() {
+            fs::write(&ini_path, &edit.contents)?;
+        }
+        Ok(edit.changed())
+    }
+}
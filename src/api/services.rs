@@ -0,0 +1,86 @@
+use serde::Serialize;
+
+use crate::api::Laracli;
+use crate::error::LaracliError;
+use crate::helpers::ipc;
+use crate::helpers::process::DevProcess;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ServiceStatus {
+    /// `php`, `nginx` or `mysql`
+    pub name: &'static str,
+    pub port: u16,
+    pub running: bool,
+    /// Restarted by the laracli service when it stops
+    pub supervised: bool,
+}
+
+/// PHP, Nginx and MySQL, and the laracli service itself
+pub struct Services<'a> {
+    laracli: &'a Laracli,
+}
+
+impl<'a> Services<'a> {
+    pub(crate) fn new(laracli: &'a Laracli) -> Services<'a> {
+        Services { laracli }
+    }
+
+    pub fn list(&self) -> Vec<ServiceStatus> {
+        let supervise = self
+            .laracli
+            .config()
+            .map(|config| config.supervise)
+            .unwrap_or_default();
+        DevProcess::ALL
+            .iter()
+            .map(|process| ServiceStatus {
+                name: process.name(),
+                port: process.port(),
+                running: process.is_running(),
+                supervised: supervise.contains(process.name()),
+            })
+            .collect()
+    }
+
+    /// Start `php`, `nginx` or `mysql` unless it is already running
+    pub fn start(&self, name: &str) -> Result<(), LaracliError> {
+        let process = dev_process(name)?;
+        self.laracli.scoped(|| {
+            if !process.is_running() {
+                process.spawn()?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Stop `php`, `nginx` or `mysql`, supervised ones are started again by the laracli service
+    pub fn stop(&self, name: &str) -> Result<(), LaracliError> {
+        let process = dev_process(name)?;
+        self.laracli.scoped(|| Ok(process.stop()?))
+    }
+
+    pub fn restart(&self, name: &str) -> Result<(), LaracliError> {
+        let process = dev_process(name)?;
+        self.laracli.scoped(|| {
+            process.stop()?;
+            process.spawn()?;
+            Ok(())
+        })
+    }
+
+    /// Status of the tasks of the laracli service, as `laracli status` shows them
+    pub fn daemon(&self) -> Result<serde_json::Value, LaracliError> {
+        let response = ipc::send(ipc::DAEMON_ENDPOINT, &ipc::Request::Status)
+            .map_err(|e| LaracliError::ServiceUnavailable(format!("laracli is not responding: {}", e)))?;
+        if !response.ok {
+            return Err(LaracliError::Other(response.message));
+        }
+        Ok(response.data)
+    }
+}
+
+fn dev_process(name: &str) -> Result<DevProcess, LaracliError> {
+    DevProcess::from_name(name).ok_or_else(|| {
+        LaracliError::InvalidArgument(format!("Unknown service {}, expected php, nginx or mysql", name))
+    })
+}
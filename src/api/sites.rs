@@ -0,0 +1,242 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::api::Laracli;
+use crate::error::LaracliError;
+use crate::helpers::config::{self, Config};
+use crate::helpers::journal::{self, EventKind};
+use crate::helpers::project::{self, ProjectType};
+use crate::helpers::linking::{self, LinkOptions};
+use crate::helpers::{hosts, ipc, nginx, sites};
+
+/// Why a site is served
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SiteSource {
+    /// In `linked_paths`, added with `laracli link`
+    Linked,
+    /// A project folder found in a watched path
+    Watched,
+    /// Proxied to a local server with `laracli proxy`
+    Proxy,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Site {
+    /// Host name without .test
+    pub name: String,
+    pub url: String,
+    /// The site and its registered subdomains, without .test
+    pub host_names: Vec<String>,
+    pub source: SiteSource,
+    /// Project folder, `None` for proxies
+    pub path: Option<PathBuf>,
+    pub kind: Option<ProjectType>,
+    pub document_root: Option<PathBuf>,
+    pub proxy_target: Option<String>,
+    pub wildcard: bool,
+}
+
+/// Linked, watched and proxied sites
+pub struct Sites<'a> {
+    laracli: &'a Laracli,
+}
+
+impl<'a> Sites<'a> {
+    pub(crate) fn new(laracli: &'a Laracli) -> Sites<'a> {
+        Sites { laracli }
+    }
+
+    /// Every served site, linked ones first
+    pub fn list(&self) -> Result<Vec<Site>, LaracliError> {
+        Ok(list_sites(&self.laracli.config()?))
+    }
+
+    /// The site served as `name`, with or without .test
+    pub fn get(&self, name: &str) -> Result<Option<Site>, LaracliError> {
+        let name = name.trim_end_matches(".test");
+        Ok(self
+            .list()?
+            .into_iter()
+            .find(|site| site.name.eq_ignore_ascii_case(name)))
+    }
+
+    /// Serve the project folder `path` as a .test site. A failed step undoes
+    /// the ones before it, the error is `RolledBack` then.
+    pub fn link(&self, path: impl AsRef<Path>, options: &LinkOptions) -> Result<Site, LaracliError> {
+        let path = path.as_ref();
+        if !path.is_dir() {
+            return Err(LaracliError::NotFound(format!("Directory does not exist: {}", path.display())));
+        }
+        if options.spa && options.kind.is_some_and(|kind| kind != ProjectType::Static) {
+            return Err(LaracliError::InvalidArgument("spa only applies to static sites".to_string()));
+        }
+        let path_str = path
+            .to_str()
+            .ok_or_else(|| LaracliError::InvalidArgument(format!("Invalid project path: {}", path.display())))?;
+
+        let name = self.laracli.scoped(|| {
+            linking::link_site(path_str, options, |request| {
+                let host_names = config::read_config()?.host_names(&config::normalize_path_string(path_str));
+                Ok(apply(request, &host_names, &[])?)
+            })
+        })?;
+
+        self.get(&name)?
+            .ok_or_else(|| LaracliError::Other(format!("{}.test was linked but is not listed", name)))
+    }
+
+    /// Stop serving a linked site or a proxy, a linked site is unlinked as `link` links it
+    pub fn unlink(&self, name: &str) -> Result<(), LaracliError> {
+        let site = self
+            .get(name)?
+            .ok_or_else(|| LaracliError::NotFound(format!("No site is served as {}.test", name)))?;
+
+        self.laracli.scoped(|| match site.source {
+            SiteSource::Watched => Err(LaracliError::InvalidArgument(format!(
+                "{}.test comes from a watched directory, unwatch it or move the folder",
+                site.name
+            ))),
+            SiteSource::Linked => {
+                let path = site.path.as_deref().unwrap_or(Path::new(""));
+                linking::unlink_site(&path.to_string_lossy(), |request| {
+                    Ok(apply(request, &[], &site.host_names)?)
+                })?;
+                Ok(())
+            }
+            SiteSource::Proxy => {
+                config::remove_proxy(&site.name)?;
+                if nginx::sites_enabled_dir()?.join(format!("{}.conf", site.name)).exists() {
+                    nginx::delete_proxy_config(&site.name)?;
+                }
+
+                let request = ipc::Request::RemoveSite {
+                    path: None,
+                    name: site.name.clone(),
+                    subdomains: Vec::new(),
+                };
+                apply(request, &[], &site.host_names)?;
+                journal::record(EventKind::SiteUnlinked, &site.name, "");
+                Ok(())
+            }
+        })
+    }
+}
+
+// Let the daemon update hosts and reload nginx, or do it here when it is not running or
+// serves another laracli directory than the handle's
+fn apply(request: ipc::Request, add: &[String], remove: &[String]) -> Result<(), LaracliError> {
+    if daemon_serves(&config::get_config_path()) {
+        match ipc::send(ipc::DAEMON_ENDPOINT, &request) {
+            Ok(response) if response.ok => return Ok(()),
+            Ok(response) => return Err(LaracliError::Other(response.message)),
+            Err(_) => {}
+        }
+    }
+    hosts::update_host_entries(add, remove)?;
+    nginx::reload_nginx()?;
+    Ok(())
+}
+
+// Whether the running daemon reads `config_path`, it applies requests to its own config.json
+fn daemon_serves(config_path: &Path) -> bool {
+    ipc::send(ipc::DAEMON_ENDPOINT, &ipc::Request::Status)
+        .ok()
+        .filter(|response| response.ok)
+        .and_then(|response| response.data.get("config")?.as_str().map(config::normalize_path_string))
+        .is_some_and(|daemon_config| daemon_config == config::normalize_path_string(&config_path.to_string_lossy()))
+}
+
+fn list_sites(config: &Config) -> Vec<Site> {
+    let mut linked: Vec<&String> = config.linked_paths.iter().collect();
+    linked.sort();
+
+    let mut list = Vec::new();
+    for path in linked {
+        let Some(name) = config.site_name(path) else {
+            continue;
+        };
+        let settings = config.sites.get(path.as_str()).cloned().unwrap_or_default();
+        let served_as = settings.kind.or(settings.spa.then_some(ProjectType::Static));
+        let project = project::resolve(Path::new(path), served_as);
+        list.push(Site {
+            url: format!("http://{}.test", name),
+            host_names: config.host_names(path),
+            source: SiteSource::Linked,
+            path: Some(PathBuf::from(path)),
+            kind: project.as_ref().map(|project| project.kind),
+            document_root: project.map(|project| project.document_root),
+            proxy_target: None,
+            wildcard: settings.wildcard,
+            name,
+        });
+    }
+
+    for (name, path) in sites::watched_projects(config) {
+        if sites::is_explicitly_linked(config, &path) {
+            continue;
+        }
+        let project = project::detect(&path);
+        list.push(Site {
+            url: format!("http://{}.test", name),
+            host_names: vec![name.clone()],
+            source: SiteSource::Watched,
+            path: Some(path),
+            kind: project.as_ref().map(|project| project.kind),
+            document_root: project.map(|project| project.document_root),
+            proxy_target: None,
+            wildcard: false,
+            name,
+        });
+    }
+
+    let mut proxies: Vec<(&String, &String)> = config.proxies.iter().collect();
+    proxies.sort();
+    for (name, target) in proxies {
+        list.push(Site {
+            name: name.clone(),
+            url: format!("http://{}.test", name),
+            host_names: vec![name.clone()],
+            source: SiteSource::Proxy,
+            path: None,
+            kind: None,
+            document_root: None,
+            proxy_target: Some(target.clone()),
+            wildcard: false,
+        });
+    }
+
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_linked_and_proxied_sites() {
+        let dir = std::env::temp_dir().join(format!("laracli-api-sites-{}", std::process::id()));
+        let blog = dir.join("blog");
+        std::fs::create_dir_all(blog.join("public")).unwrap();
+        std::fs::write(blog.join("artisan"), "").unwrap();
+
+        let mut config = Config::default();
+        let blog_path = blog.to_string_lossy().into_owned();
+        config.linked_paths.insert(blog_path.clone());
+        config.sites.entry(blog_path).or_default().subdomains.push("api".to_string());
+        config.proxies.insert("vite".to_string(), "http://127.0.0.1:5173".to_string());
+
+        let sites = list_sites(&config);
+        assert_eq!(sites.len(), 2);
+        assert_eq!(sites[0].name, "blog");
+        assert_eq!(sites[0].source, SiteSource::Linked);
+        assert_eq!(sites[0].host_names, vec!["blog", "api.blog"]);
+        assert_eq!(sites[0].kind, Some(ProjectType::Laravel));
+        assert_eq!(sites[0].document_root, Some(blog.join("public")));
+        assert_eq!(sites[1].source, SiteSource::Proxy);
+        assert_eq!(sites[1].proxy_target.as_deref(), Some("http://127.0.0.1:5173"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -12,15 +12,41 @@ pub enum Commands {
     Unwatch(Unwatch),
     Link(Link),
     Unlink(Unlink),
+    Subdomain(Subdomain),
+    Proxy(Proxy),
     Setup(Setup),
+    Uninstall(Uninstall),
     StartDev(Run),
     StopDev(StopDev),
+    Status(Status),
+    Sync(Sync),
+    Logs(Logs),
+    History(History),
     Version(Version),
     Enable(Enable),
-    // Disable(Disable),
+    Disable(Disable),
+    Features(Features),
     PhpExtension(PhpExtension),
 }
 
+impl Commands {
+    /// `--dry-run` of the commands that record their changes instead of making them
+    pub fn dry_run(&self) -> bool {
+        match self {
+            Commands::Link(link) => link.dry_run,
+            Commands::Unlink(unlink) => unlink.dry_run,
+            Commands::Watch(watch) => watch.dry_run,
+            Commands::Setup(setup) => setup.dry_run,
+            Commands::Enable(Enable { feature: Feature::PhpMyAdmin(feature) })
+            | Commands::Disable(Disable { feature: Feature::PhpMyAdmin(feature) }) => feature.dry_run,
+            Commands::PhpExtension(PhpExtension {
+                action: PhpExtensionAction::Enable(enable),
+            }) => enable.dry_run,
+            _ => false,
+        }
+    }
+}
+
 #[derive(FromArgs, Debug)]
 /// Start a service (nginx or mysql)
 #[argh(subcommand, name = "start")]
@@ -70,13 +96,28 @@ pub struct NginxReload {}
 /// MySQL service
 pub struct Mysql {}
 
-/// Watch for new Laravel projects and auto-add hosts
+/// Watch for new Laravel projects and serve them at <folder>.test
 #[derive(FromArgs, Debug)]
 #[argh(subcommand, name = "watch")]
 pub struct Watch {
     /// path to watch (e.g., C:\www)
     #[argh(positional)]
     pub path: String,
+    /// also set APP_URL in the .env of linked projects
+    #[argh(switch)]
+    pub update_env: bool,
+    /// how many levels below the path projects are searched (default 1)
+    #[argh(option)]
+    pub depth: Option<usize>,
+    /// only serve folders matching this glob, can be repeated
+    #[argh(option)]
+    pub include: Vec<String>,
+    /// skip folders matching this glob, can be repeated
+    #[argh(option)]
+    pub exclude: Vec<String>,
+    /// only show the changes that would be made
+    #[argh(switch)]
+    pub dry_run: bool,
 }
 
 /// List all currently watched directories
@@ -100,26 +141,130 @@ pub struct Link {
     /// path to link (e.g., C:\www/laravel)
     #[argh(positional)]
     pub path: String,
+    /// also serve every subdomain (*.name.test)
+    #[argh(switch)]
+    pub wildcard: bool,
+    /// serve as this project type instead of the detected one: laravel, symfony, wordpress, statamic, php or static
+    #[argh(option, long = "type")]
+    pub kind: Option<String>,
+    /// static single-page app: unknown paths are answered with index.html
+    #[argh(switch)]
+    pub spa: bool,
+    /// only show the changes that would be made
+    #[argh(switch)]
+    pub dry_run: bool,
 }
 
-/// Unlink an existing directory
+/// Unlink an existing directory or proxied site
 #[derive(FromArgs, Debug)]
 #[argh(subcommand, name = "unlink")]
 pub struct Unlink {
-    /// path to unlink (e.g., C:\www/laravel)
+    /// path to unlink (e.g., C:\www/laravel), or the name of a proxied site
     #[argh(positional)]
     pub path: String,
+    /// only show the changes that would be made
+    #[argh(switch)]
+    pub dry_run: bool,
+}
+
+/// Serve a .test site that proxies to a local server (Vite, Node, Reverb)
+#[derive(FromArgs, Debug)]
+#[argh(subcommand, name = "proxy")]
+pub struct Proxy {
+    /// site name without .test (e.g., vite)
+    #[argh(positional)]
+    pub name: String,
+    /// server to proxy to (e.g., http://127.0.0.1:5173 or 5173)
+    #[argh(positional)]
+    pub target: String,
+}
+
+/// Manage subdomains of a linked site
+#[derive(FromArgs, Debug)]
+#[argh(subcommand, name = "subdomain")]
+pub struct Subdomain {
+    /// subdomain subcommand: add or remove
+    #[argh(subcommand)]
+    pub action: SubdomainAction,
+}
+
+#[derive(FromArgs, Debug)]
+#[argh(subcommand)]
+pub enum SubdomainAction {
+    Add(AddSubdomain),
+    Remove(RemoveSubdomain),
+}
+
+#[derive(FromArgs, Debug)]
+#[argh(subcommand, name = "add")]
+/// Serve a subdomain of a site and add its host entry
+pub struct AddSubdomain {
+    /// site name without .test (e.g., shop)
+    #[argh(positional)]
+    pub site: String,
+    /// subdomain to add (e.g., acme for acme.shop.test)
+    #[argh(positional)]
+    pub subdomain: String,
+}
+
+#[derive(FromArgs, Debug)]
+#[argh(subcommand, name = "remove")]
+/// Stop serving a subdomain of a site and remove its host entry
+pub struct RemoveSubdomain {
+    /// site name without .test (e.g., shop)
+    #[argh(positional)]
+    pub site: String,
+    /// subdomain to remove (e.g., acme)
+    #[argh(positional)]
+    pub subdomain: String,
 }
 
 /// Setup and install services with necessary permissions
 #[derive(FromArgs, Debug)]
 #[argh(subcommand, name = "setup")]
-pub struct Setup {}
+pub struct Setup {
+    /// only show the changes that would be made
+    #[argh(switch)]
+    pub dry_run: bool,
+}
+
+/// Stop and remove services, PATH entry, host entries and downloaded tools
+#[derive(FromArgs, Debug)]
+#[argh(subcommand, name = "uninstall")]
+pub struct Uninstall {
+    /// keep downloaded tools (nginx, php, mysql)
+    #[argh(switch)]
+    pub keep_tools: bool,
+    /// keep MySQL databases and the laracli config
+    #[argh(switch)]
+    pub keep_data: bool,
+    /// do not ask for confirmation
+    #[argh(switch, short = 'y')]
+    pub yes: bool,
+}
 
 /// start php nginx and mysql services
 #[derive(FromArgs, Debug)]
 #[argh(subcommand, name = "start-dev")]
-pub struct Run {}
+pub struct Run {
+    /// keep running and restart them when they stop
+    #[argh(switch)]
+    pub supervise: bool,
+}
+
+/// Show whether the laracli service is running
+#[derive(FromArgs, Debug)]
+#[argh(subcommand, name = "status")]
+pub struct Status {}
+
+/// Fix nginx configs and hosts entries that drifted from config.json
+#[derive(FromArgs, Debug)]
+#[argh(subcommand, name = "sync")]
+pub struct Sync {
+    /// only show the changes that would be made
+    #[argh(switch)]
+    pub dry_run: bool,
+}
 
 /// Show version information
 #[derive(FromArgs, Debug)]
@@ -148,12 +293,37 @@ pub struct Disable {
 #[argh(subcommand)]
 pub enum Feature {
     PhpMyAdmin(PhpMyAdmin),
+    Dns(Dns),
+}
+
+impl Feature {
+    /// Name of the feature in the feature registry
+    pub fn name(&self) -> &'static str {
+        match self {
+            Feature::PhpMyAdmin(_) => "phpmyadmin",
+            Feature::Dns(_) => "dns",
+        }
+    }
 }
 
 #[derive(FromArgs, Debug)]
 #[argh(subcommand, name = "phpmyadmin")]
 /// phpMyAdmin feature
-pub struct PhpMyAdmin {}
+pub struct PhpMyAdmin {
+    /// only show the changes that would be made
+    #[argh(switch)]
+    pub dry_run: bool,
+}
+
+#[derive(FromArgs, Debug)]
+#[argh(subcommand, name = "dns")]
+/// built-in DNS resolver for .test domains
+pub struct Dns {}
+
+/// List optional features and whether they are enabled
+#[derive(FromArgs, Debug)]
+#[argh(subcommand, name = "features")]
+pub struct Features {}
 
 /// Manage PHP extensions
 #[derive(FromArgs, Debug)]
@@ -178,6 +348,9 @@ pub struct EnablePhpExtension {
     /// name of the PHP extension to enable (e.g., mbstring)
     #[argh(positional)]
     pub extension: String,
+    /// only show the changes that would be made
+    #[argh(switch)]
+    pub dry_run: bool,
 }
 
 #[derive(FromArgs, Debug)]
@@ -189,6 +362,39 @@ pub struct DisablePhpExtension {
     pub extension: String,
 }
 
+/// Show the logs of a site, nginx, php, mysql or laracli itself
+#[derive(FromArgs, Debug)]
+#[argh(subcommand, name = "logs")]
+pub struct Logs {
+    /// site name (e.g., blog), nginx, php, mysql, service or cli
+    #[argh(positional)]
+    pub source: String,
+    /// keep printing new lines as they are written
+    #[argh(switch, short = 'f')]
+    pub follow: bool,
+    /// how many lines to show from the end of each file (default 50)
+    #[argh(option, short = 'n', default = "50")]
+    pub lines: usize,
+}
+
+/// Show what laracli and its service changed: linked sites, host entries, reloads, restarts and errors
+#[derive(FromArgs, Debug)]
+#[argh(subcommand, name = "history")]
+pub struct History {
+    /// only this kind of event: site-linked, site-unlinked, host-added, host-removed, nginx-reloaded, process-restarted, service-started, service-stopped or error
+    #[argh(option)]
+    pub kind: Option<String>,
+    /// only events about this site (e.g., blog)
+    #[argh(option)]
+    pub site: Option<String>,
+    /// only events newer than this (e.g., 30m, 12h, 7d)
+    #[argh(option)]
+    pub since: Option<String>,
+    /// how many of the newest events to show (default 50)
+    #[argh(option, short = 'n', default = "50")]
+    pub limit: usize,
+}
+
 /// Stop php nginx and mysql services
 #[derive(FromArgs, Debug)]
 #[argh(subcommand, name = "stop-dev")]
@@ -210,6 +416,14 @@ pub struct StopDev {}
 /// laracli
 #[argh(description = "Simple tool to manage Laravel's or php projects")]
 pub struct Cli {
+    /// print one JSON result object instead of text
+    #[argh(switch)]
+    pub json: bool,
+
+    /// print nothing but errors
+    #[argh(switch, short = 'q')]
+    pub quiet: bool,
+
     #[argh(subcommand)]
     pub command: Commands,
 }
\ No newline at end of file
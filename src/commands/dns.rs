@@ -0,0 +1,109 @@
+use colored::Colorize;
+use std::process::Command;
+use laracli::utils::elevate;
+use laracli::helpers::{self, dns, ipc};
+use laracli::say;
+
+// Windows sends queries for the TLD to our resolver through a name resolution policy rule.
+// The TLD is the fixed `dns::SITE_TLD`, nothing from config.json goes into the command
+fn add_nrpt_rule(tld: &str, port: u16) -> Result<(), Box<dyn std::error::Error>> {
+    if port != 53 {
+        say!(
+            "{}",
+            format!("⚠ Windows only queries port 53, resolve *.{} against 127.0.0.1:{} yourself.", tld, port).yellow()
+        );
+        return Ok(());
+    }
+    let script = format!(
+        "if (-not (Get-DnsClientNrptRule | Where-Object {{ $_.Namespace -eq '.{0}' }})) {{ Add-DnsClientNrptRule -Namespace '.{0}' -NameServers '127.0.0.1' }}",
+        tld
+    );
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-Command", &script])
+        .output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to add the DNS policy for .{}: {}",
+            tld,
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+    Ok(())
+}
+
+fn remove_nrpt_rule(tld: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let script = format!(
+        "Get-DnsClientNrptRule | Where-Object {{ $_.Namespace -eq '.{}' }} | Remove-DnsClientNrptRule -Force",
+        tld
+    );
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-Command", &script])
+        .output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to remove the DNS policy for .{}: {}",
+            tld,
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+    Ok(())
+}
+
+// Tell the daemon to start or stop its resolver
+fn reload_daemon() {
+    match ipc::send(ipc::DAEMON_ENDPOINT, &ipc::Request::ReloadConfig) {
+        Ok(response) if response.ok => {}
+        Ok(response) => say!("{}", format!("⚠ {}", response.message).yellow()),
+        Err(e) => say!("{}", format!("⚠ laracli service not reachable: {}", e).yellow()),
+    }
+}
+
+pub fn enable_dns() -> Result<(), Box<dyn std::error::Error>> {
+    if !elevate::is_elevated() {
+        say!("🔒 Elevation required to change the DNS policy. Requesting UAC permission...");
+        elevate::run_as_admin()?;
+        return Ok(());
+    }
+
+    let mut config = helpers::config::load_config()?;
+    config.dns.enabled = true;
+    helpers::config::write_config(&config)?;
+    add_nrpt_rule(dns::SITE_TLD, config.dns.port)?;
+    reload_daemon();
+
+    // Host functions skip the hosts file from now on, so the old entries are removed here
+    match helpers::hosts::remove_laracli_host_entries() {
+        Ok(0) => {}
+        Ok(count) => say!("✔ Removed {} host entries, the resolver answers for them", count),
+        Err(e) => say!("{}", format!("⚠ Could not remove the laracli host entries: {}", e).yellow()),
+    }
+
+    say!(
+        "{}",
+        format!("✔ *.{} now resolves through the laracli service, hosts entries are no longer added.", dns::SITE_TLD).green()
+    );
+    Ok(())
+}
+
+pub fn disable_dns() -> Result<(), Box<dyn std::error::Error>> {
+    if !elevate::is_elevated() {
+        say!("🔒 Elevation required to change the DNS policy. Requesting UAC permission...");
+        elevate::run_as_admin()?;
+        return Ok(());
+    }
+
+    let mut config = helpers::config::load_config()?;
+    config.dns.enabled = false;
+    helpers::config::write_config(&config)?;
+    remove_nrpt_rule(dns::SITE_TLD)?;
+    reload_daemon();
+
+    say!("{}", "✔ DNS resolver disabled, run `laracli sync` to add hosts entries again.".green());
+    Ok(())
+}
+
+pub fn dns_enabled() -> bool {
+    helpers::config::read_config().is_ok_and(|config| config.dns.enabled)
+}
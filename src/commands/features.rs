@@ -0,0 +1,145 @@
+use colored::Colorize;
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::commands::{dns, phpmyadmin};
+use laracli::say;
+use laracli::helpers::output;
+
+pub type FeatureFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<(), Box<dyn std::error::Error>>> + 'a>>;
+
+pub struct FeatureStatus {
+    /// Files for the feature are present in tools/
+    pub installed: bool,
+    /// The feature is configured and served by nginx
+    pub enabled: bool,
+}
+
+/// An optional add-on that can be enabled and disabled with
+/// `laracli enable <name>` / `laracli disable <name>`
+pub trait Feature {
+    fn name(&self) -> &'static str;
+    fn description(&self) -> &'static str;
+    /// Download (if needed), configure nginx and link the feature
+    fn enable(&self) -> FeatureFuture<'_>;
+    /// Remove nginx config, host entry and linked path of the feature
+    fn disable(&self) -> Result<(), Box<dyn std::error::Error>>;
+    fn status(&self) -> FeatureStatus;
+    /// Address where the feature can be reached once enabled
+    fn url(&self) -> Option<String> {
+        None
+    }
+}
+
+pub struct PhpMyAdmin;
+
+impl Feature for PhpMyAdmin {
+    fn name(&self) -> &'static str {
+        "phpmyadmin"
+    }
+
+    fn description(&self) -> &'static str {
+        "Web interface for the bundled MySQL server"
+    }
+
+    fn enable(&self) -> FeatureFuture<'_> {
+        Box::pin(phpmyadmin::enable_phpmyadmin())
+    }
+
+    fn disable(&self) -> Result<(), Box<dyn std::error::Error>> {
+        phpmyadmin::disable_phpmyadmin()
+    }
+
+    fn status(&self) -> FeatureStatus {
+        FeatureStatus {
+            installed: phpmyadmin::phpmyadmin_installed(),
+            enabled: phpmyadmin::phpmyadmin_enabled(),
+        }
+    }
+
+    fn url(&self) -> Option<String> {
+        Some("http://phpmyadmin.test".to_string())
+    }
+}
+
+pub struct Dns;
+
+impl Feature for Dns {
+    fn name(&self) -> &'static str {
+        "dns"
+    }
+
+    fn description(&self) -> &'static str {
+        "Built-in resolver for the site TLD instead of hosts entries"
+    }
+
+    fn enable(&self) -> FeatureFuture<'_> {
+        Box::pin(async { dns::enable_dns() })
+    }
+
+    fn disable(&self) -> Result<(), Box<dyn std::error::Error>> {
+        dns::disable_dns()
+    }
+
+    fn status(&self) -> FeatureStatus {
+        // Part of the laracli service, nothing to download
+        FeatureStatus {
+            installed: true,
+            enabled: dns::dns_enabled(),
+        }
+    }
+}
+
+/// All features known to laracli
+pub fn registry() -> Vec<Box<dyn Feature>> {
+    vec![Box::new(PhpMyAdmin), Box::new(Dns)]
+}
+
+pub fn find(name: &str) -> Result<Box<dyn Feature>, Box<dyn std::error::Error>> {
+    registry()
+        .into_iter()
+        .find(|feature| feature.name() == name)
+        .ok_or_else(|| format!("Unknown feature: {}", name).into())
+}
+
+pub async fn enable(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    find(name)?.enable().await
+}
+
+pub fn disable(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let feature = find(name)?;
+    if !feature.status().enabled {
+        say!("{}", format!("ℹ {} is not enabled.", feature.name()).blue());
+        return Ok(());
+    }
+    feature.disable()
+}
+
+pub fn list_features() {
+    say!("{}", "Available features:".yellow());
+    let mut features = Vec::new();
+    for feature in registry() {
+        let status = feature.status();
+        features.push(serde_json::json!({
+            "name": feature.name(),
+            "installed": status.installed,
+            "enabled": status.enabled,
+            "url": feature.url(),
+        }));
+        let state = if status.enabled {
+            "enabled".green()
+        } else if status.installed {
+            "installed, disabled".yellow()
+        } else {
+            "not installed".dimmed()
+        };
+        say!("  {:<12} {} - {}", feature.name().bright_cyan(), state, feature.description());
+        if status.enabled
+            && let Some(url) = feature.url()
+        {
+            say!("  {:<12} {}", "", url.blue());
+        }
+    }
+    output::set("features", features);
+}
@@ -0,0 +1,54 @@
+use colored::Colorize;
+
+use laracli::helpers::journal::{self, EventKind, Filter};
+use laracli::say;
+use laracli::helpers::output;
+
+/// Print the journal of changes made by laracli and its service
+pub fn history(
+    kind: Option<&str>,
+    site: Option<&str>,
+    since: Option<&str>,
+    limit: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let kind = match kind {
+        Some(name) => Some(
+            serde_json::from_value::<EventKind>(serde_json::Value::String(name.to_string()))
+                .map_err(|_| format!("Unknown event kind: {}", name))?,
+        ),
+        None => None,
+    };
+    let filter = Filter {
+        kind,
+        subject: site.map(|site| site.trim_end_matches(".test").to_string()),
+        since: since.map(journal::parse_since).transpose()?,
+        limit: Some(limit),
+    };
+
+    let events = journal::read(&filter);
+    output::set("events", &events);
+    if events.is_empty() {
+        say!("{}", "No matching events.".yellow());
+        return Ok(());
+    }
+
+    for event in events {
+        let time = event
+            .time()
+            .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or(event.time.clone());
+        let kind = format!("{:<17}", event.kind.name());
+        let kind = match event.kind {
+            EventKind::Error => kind.red(),
+            EventKind::SiteLinked | EventKind::HostAdded | EventKind::ServiceStarted => kind.green(),
+            EventKind::SiteUnlinked | EventKind::HostRemoved | EventKind::ServiceStopped => kind.yellow(),
+            _ => kind.blue(),
+        };
+        let mut line = format!("{} {} {}", time.dimmed(), kind, event.subject);
+        if !event.detail.is_empty() {
+            line.push_str(&format!(" {}", event.detail.dimmed()));
+        }
+        say!("{} {}", line, format!("({})", event.source).dimmed());
+    }
+    Ok(())
+}
@@ -4,6 +4,7 @@ use windows_service::{
     },
     service_manager::{ServiceManager, ServiceManagerAccess},
 };
+use laracli::say;
 
 pub fn install_service() -> Result<(), Box<dyn std::error::Error>> {
     let service_binary_path = std::env::current_exe()?.with_file_name("laracli-service.exe");
@@ -24,6 +25,6 @@ pub fn install_service() -> Result<(), Box<dyn std::error::Error>> {
 
     manager.create_service(&service_info, windows_service::service::ServiceAccess::all())?;
 
-    println!("✅ Service installed: LaracliWatcher");
+    say!("✅ Service installed: LaracliWatcher");
     Ok(())
 }
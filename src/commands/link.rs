@@ -1,91 +1,168 @@
 use std::path::Path;
-use crate::helpers;
-use windows_service::service::{ServiceAccess, ServiceState};
-use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
-use std::time::Duration;
+use laracli::helpers;
+use laracli::helpers::ipc;
+use laracli::helpers::linking::{self, LinkOptions};
+use laracli::helpers::project::ProjectType;
+use laracli::say;
+use laracli::error::LaracliError;
+use laracli::helpers::{output, plan};
 
-pub fn link(path: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let path = Path::new(path);
-    let name = path.file_name().unwrap().to_str().unwrap();
+pub fn link(path: &str, wildcard: bool, kind: Option<&str>, spa: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let kind = match kind {
+        Some(name) => Some(ProjectType::from_name(name).ok_or_else(|| {
+            let names: Vec<&str> = ProjectType::ALL.iter().map(|kind| kind.name()).collect();
+            LaracliError::InvalidArgument(format!(
+                "Unknown project type {}, expected one of: {}",
+                name,
+                names.join(", ")
+            ))
+        })?),
+        None => None,
+    };
+    if spa && kind.is_some_and(|kind| kind != ProjectType::Static) {
+        return Err(LaracliError::InvalidArgument("--spa only applies to static sites".to_string()).into());
+    }
 
-    println!("Linking {}, path: {}", name, path.display());
-    let config_path = helpers::config::get_config_path();
-    println!("Using config: {}", config_path.display());
+    let path_str = path;
+    let path = Path::new(path_str);
+    let name = site_name(path)?;
 
-    // Add to linked_paths in config
-    helpers::config::add_to_linked_paths(path.to_str().unwrap());
-    println!("✅ Updated config with linked path: {}", path.display());
+    say!("Linking {}, path: {}", name, path.display());
+    let config_path = helpers::config::get_config_path();
+    say!("Using config: {}", config_path.display());
 
-    // Create nginx config immediately
-    match helpers::nginx::create_nginx_config(path.to_str().unwrap(), None) {
-        Ok(()) => println!("✅ Nginx config created"),
-        Err(e) => println!("❌ Error creating Nginx config: {}", e),
+    let served_as = kind.or(spa.then_some(ProjectType::Static));
+    let project = helpers::project::resolve(path, served_as);
+    match &project {
+        Some(project) if served_as.is_some() => {
+            say!("Serving as {} site from {}", project.kind, project.document_root.display())
+        }
+        Some(project) => say!("Detected {} project, serving {}", project.kind, project.document_root.display()),
+        None => say!("⚠️ No known project type detected, serving it as a PHP site"),
+    }
+    if spa {
+        say!("   Unknown paths fall back to index.html");
     }
 
-    // Restart laracli_config service to process config changes
-    restart_config_service()?;
+    // Config, nginx config, then the daemon adds the host entry and reloads nginx;
+    // a failed step undoes the ones before it
+    let options = LinkOptions { kind, wildcard, spa };
+    linking::link_site(path_str, &options, notify_daemon).inspect_err(report_rollback)?;
+    say!("✅ Updated config with linked path: {}", path.display());
+    say!("✅ Nginx config created");
 
-    println!("✅ Project linked! The service will now monitor this directory.");
-    println!("   - Host entry: {}.test -> 127.0.0.1 (will be added by service)", name);
-    println!("   - Nginx config: Created and will be reloaded by service");
-    println!("   - Service monitoring: If you move/delete this directory, entries will be automatically cleaned up");
+    say!("✅ Project linked! The service will now monitor this directory.");
+    let addresses: Vec<String> = helpers::config::load_config()?
+        .network
+        .host_addresses()
+        .iter()
+        .map(|address| address.to_string())
+        .collect();
+    say!("   - Host entry: {}.test -> {}", name, addresses.join(", "));
+    if wildcard {
+        if helpers::config::load_config()?.dns.enabled {
+            say!("   - Subdomains: *.{}.test resolved by the DNS resolver", name);
+        } else {
+            // The hosts file cannot hold wildcards
+            say!("   - Subdomains: *.{}.test is served, but only resolves with `laracli enable dns`", name);
+            say!("     or add single subdomains with `laracli subdomain add {} <subdomain>`", name);
+        }
+    }
+    output::set(
+        "site",
+        serde_json::json!({
+            "name": name,
+            "host": format!("{}.test", name),
+            "path": path,
+            "type": project.as_ref().map(|project| project.kind.name()),
+            "document_root": project.as_ref().map(|project| &project.document_root),
+            "wildcard": wildcard,
+            "spa": spa,
+            "addresses": addresses,
+        }),
+    );
+    say!("   - Nginx config: Created and reloaded");
+    say!("   - Service monitoring: If you move/delete this directory, entries will be automatically cleaned up");
 
     Ok(())
 }
 
 pub fn unlink(path: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let path = Path::new(path);
-    let name = path.file_name().unwrap().to_str().unwrap();
+    // `laracli unlink vite` removes a proxied site
+    let name = path.trim_end_matches(".test").to_lowercase();
+    if !Path::new(path).exists() && helpers::config::load_config()?.proxies.contains_key(&name) {
+        return crate::commands::proxy::unproxy(&name);
+    }
+
+    let path_str = path;
+    let path = Path::new(path_str);
+    let name = site_name(path)?;
 
-    println!("Unlinking {}, path: {}", name, path.display());
+    say!("Unlinking {}, path: {}", name, path.display());
     let config_path = helpers::config::get_config_path();
-    println!("Using config: {}", config_path.display());
+    say!("Using config: {}", config_path.display());
 
-    // Remove from linked_paths in config
-    helpers::config::remove_from_linked_paths(path.to_str().unwrap()).expect("Failed to remove path from config");
-    println!("✅ Removed linked path from config: {}", path.display());
+    // Config, nginx config, then the daemon removes the host entries and reloads nginx;
+    // a failed step undoes the ones before it
+    let host_name = linking::unlink_site(path_str, notify_daemon).inspect_err(report_rollback)?;
+    say!("✅ Removed linked path from config: {}", path.display());
+    say!("✅ Nginx config deleted");
 
-    // Remove nginx config immediately
-    match helpers::nginx::delete_nginx_config(path.to_str().unwrap()) {
-        Ok(()) => println!("✅ Nginx config deleted"),
-        Err(e) => println!("❌ Error deleting Nginx config: {}", e),
-    }
+    say!("✅ Project unlinked! Service will no longer monitor this directory.");
+    output::set("site", serde_json::json!({ "name": host_name, "path": path }));
 
-    // Restart laracli_config service to process config changes
-    crate::commands::nginx::reload()?;
+    Ok(())
+}
 
-    println!("✅ Project unlinked! Service will no longer monitor this directory.");
+// Folder name a path is served under, a drive root like `C:\` has none
+fn site_name(path: &Path) -> Result<&str, LaracliError> {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| {
+            LaracliError::InvalidArgument(format!(
+                "{} is not a project folder, link a folder like C:\\www\\blog",
+                path.display()
+            ))
+        })
+}
 
-    Ok(())
+// Keep what a failed link or unlink undid in the JSON result
+pub fn report_rollback(error: &LaracliError) {
+    if let LaracliError::RolledBack { step, undone, not_undone, .. } = error {
+        output::set(
+            "rolled_back",
+            serde_json::json!({ "failed_step": step, "undone": undone, "not_undone": not_undone }),
+        );
+    }
 }
 
-fn restart_config_service() -> Result<(), Box<dyn std::error::Error>> {
-    let service_name = "laracli_config";
-    let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
-    
-    if let Ok(service) = manager.open_service(service_name, ServiceAccess::START | ServiceAccess::STOP | ServiceAccess::QUERY_STATUS) {
-        println!("🔄 Restarting laracli_config service to apply configuration changes...");
-        
-        // Stop the service
-        if let Err(e) = service.stop() {
-            println!("Note: Error stopping service (might not be running): {}", e);
+// Send a request to the laracli daemon. Runs inside transaction steps, so it never restarts
+// the service or elevates, which would exit before the rollback
+pub fn notify_daemon(request: ipc::Request) -> Result<(), Box<dyn std::error::Error>> {
+    if plan::is_dry_run() {
+        return apply_daemon_request(&request);
+    }
+    match ipc::send(ipc::DAEMON_ENDPOINT, &request) {
+        Ok(response) if response.ok => {
+            say!("✅ {}", response.message);
+            Ok(())
         }
-        
-        // Wait for service to stop
-        for _ in 0..10 {
-            let status = service.query_status()?;
-            if status.current_state == ServiceState::Stopped {
-                break;
-            }
-            std::thread::sleep(Duration::from_millis(500));
+        Ok(response) => Err(response.message.into()),
+        // The service is not running, update the hosts file and reload nginx here
+        Err(_) => apply_daemon_request(&request),
+    }
+}
+
+// What the daemon does for AddSite and RemoveSite, done here during a dry run so the plan
+// shows it, or when the service is not running
+pub fn apply_daemon_request(request: &ipc::Request) -> Result<(), Box<dyn std::error::Error>> {
+    match request {
+        ipc::Request::AddSite { name, .. } => helpers::hosts::add_host_entry(name)?,
+        ipc::Request::RemoveSite { name, subdomains, .. } => {
+            // Subdomains registered for the site go with it
+            helpers::hosts::update_host_entries(&[], &helpers::hosts::site_host_names(name, subdomains))?;
         }
-        
-        // Start the service
-        service.start::<&str>(&[])?;
-        println!("✅ laracli_config service restarted successfully.");
-    } else {
-        println!("⚠️ Could not restart laracli_config service. Changes may not take effect until service restarts.");
+        _ => {}
     }
-    
-    Ok(())
-}
\ No newline at end of file
+    helpers::nginx::reload_nginx()
+}
@@ -0,0 +1,57 @@
+use colored::Colorize;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use laracli::helpers;
+use laracli::helpers::logs::{self, LogFollower, LogSource};
+use laracli::say;
+use laracli::helpers::output;
+
+/// Print the last lines of a site, nginx, php, mysql or service log, then keep following it
+pub fn logs(source: &str, lines: usize, follow: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let config = helpers::config::load_config()?;
+    let files = logs::log_files(&LogSource::parse(source), &config)?;
+    if follow && !output::is_text() {
+        return Err("--follow only works with text output".into());
+    }
+
+    let mut tails = serde_json::Map::new();
+    for file in &files {
+        say!("{}", format!("==> {} <==", file.display()).blue());
+        match logs::tail_lines(file, lines) {
+            Ok(tail) => {
+                for line in &tail {
+                    say!("{}", line);
+                }
+                tails.insert(file.display().to_string(), tail.into());
+            }
+            Err(_) => say!("{}", "(no log yet)".dimmed()),
+        }
+        say!();
+    }
+    output::set("files", tails);
+
+    if !follow {
+        return Ok(());
+    }
+
+    say!("{}", "Following, press Ctrl+C to stop.".yellow());
+    let mut follower = LogFollower::new(&files);
+    loop {
+        for (file, line) in follower.poll() {
+            if files.len() > 1 {
+                say!("{} {}", format!("[{}]", file_name(&file)).blue(), line);
+            } else {
+                say!("{}", line);
+            }
+        }
+        thread::sleep(Duration::from_millis(500));
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
@@ -1,4 +1,5 @@
 use std::io;
+use std::net::SocketAddr;
 use std::path::Path;
 use std::process::Command;
 use std::process::Child;
@@ -7,57 +8,50 @@ use std::process::Child;
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
 
-use chrono::format;
 use colored::Colorize;
 use laracli::helpers;
+use laracli::say;
 
-pub fn start() -> Result<Child, std::io::Error>  {
-    println!("{}", "Starting MySQL service...".yellow());
-
-    //check if mysql already running
-
-    let mysql_path = helpers::path::get_mysql_path().unwrap();
-    let mysql_bin = Path::new(&mysql_path).join("bin/mysqld.exe");
-    let ini_file = Path::new(&mysql_path).join("my.ini");
-
-    if !mysql_bin.exists() {
-        return Err(std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            format!("MySQL binary not found at {}", mysql_bin.to_str().unwrap()),
-        ));
-    } else if !ini_file.exists() {
-        return Err(std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            format!("MySQL ini file not found at {}", ini_file.to_str().unwrap()),
-        ));
-    }
+pub fn start() -> Result<Child, Box<dyn std::error::Error>> {
+    say!("{}", "Starting MySQL service...".yellow());
+
+    let mysql_path = helpers::path::get_mysql_path()?;
+    let mysql_bin = helpers::path::require_tool("MySQL", Path::new(&mysql_path).join("bin/mysqld.exe"))?;
+    let ini_file = helpers::path::require_tool("MySQL", Path::new(&mysql_path).join("my.ini"))?;
+
+    // mysqld exits silently when its port is taken, e.g. by another MySQL
+    helpers::network::ensure_port_free(
+        "MySQL",
+        SocketAddr::from(([127, 0, 0, 1], helpers::mysql::MYSQL_PORT)),
+    )?;
 
     let child = Command::new(mysql_bin)
-        .arg(format!("--defaults-file={}", ini_file.to_str().unwrap()))
+        .arg(format!("--defaults-file={}", ini_file.display()))
         .arg("--console")
         .creation_flags(0x08000000) // DETACHED_PROCESS on Windows
         .spawn()?;
 
 
-    println!("{}", "✔ MySQL service started successfully.".green());
+    say!("{}", "✔ MySQL service started successfully.".green());
 
     Ok(child)
 }
 
 pub fn stop() -> Result<(), io::Error> {
-    println!("{}", "Stopping MySQL service...".yellow());
+    say!("{}", "Stopping MySQL service...".yellow());
     let output = Command::new("taskkill")
-        .args(&["/F", "/IM", "mysqld.exe"])
+        .args(["/F", "/IM", "mysqld.exe"])
         .output()?;
 
     if output.status.success() {
-        println!("{}", "✔ MySQL service stopped successfully.".green());
+        say!("{}", "✔ MySQL service stopped successfully.".green());
         Ok(())
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(io::Error::new(
-            io::ErrorKind::Other,
-            format!("{}: {}", "Failed to stop MySQL service".red(), stderr),
-        ))
+        Err(io::Error::other(format!(
+            "{}: {}",
+            "Failed to stop MySQL service".red(),
+            stderr
+        )))
     }
 }
\ No newline at end of file
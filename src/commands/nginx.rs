@@ -1,6 +1,7 @@
 use colored::Colorize;
 use std::process::Command;
-use crate::helpers::path;
+use laracli::helpers::{config, network, path};
+use laracli::say;
 use std::os::windows::process::CommandExt;
 use std::path::Path;
 
@@ -8,23 +9,20 @@ use std::path::Path;
 
 #[cfg(target_os = "windows")]
 pub fn start() -> Result<(), Box<dyn std::error::Error>> {
-    //check if nginx is already running by checking pid file at logs/
-
-    let pid_file_path = format!("{}/logs/nginx.pid", path::get_nginx_path()?);
+    // Check if nginx is already running
     let output = Command::new("sc")
-        .args(&["query", "nginx"])
+        .args(["query", "nginx"])
         .output()?;
     
     if output.status.success() && String::from_utf8_lossy(&output.stdout).contains("RUNNING") {
-        println!("{}", "✔ Nginx service is already running.".green());
+        say!("{}", "✔ Nginx service is already running.".green());
         return Ok(());
     }
 
-
-
-
     let nginx_path = path::get_nginx_path()?;
-    let nginx_exe = format!("{}/nginx.exe", &nginx_path);
+    let nginx_exe = path::require_tool("Nginx", Path::new(&nginx_path).join("nginx.exe"))?;
+    let address = config::load_config()?.network.listen_address(network::HTTP_PORT);
+    network::ensure_port_free("Nginx", address)?;
 
     Command::new(&nginx_exe)
         .current_dir(&nginx_path) // Sets working dir so relative paths like "conf/nginx.conf" work
@@ -35,7 +33,7 @@ pub fn start() -> Result<(), Box<dyn std::error::Error>> {
         .creation_flags(0x08000000) // DETACHED_PROCESS on Windows
         .spawn()?; // Don't wait on it
 
-    println!("{}", "✔ Nginx service started successfully.".green());
+    say!("{}", "✔ Nginx service started successfully.".green());
     Ok(())
 }
 
@@ -45,11 +43,11 @@ pub fn stop() -> Result<(), Box<dyn std::error::Error>> {
     let pid_file_path = format!("{}/logs/nginx.pid", path::get_nginx_path()?);
     let pid_file = Path::new(&pid_file_path);
     if !pid_file.exists() {
-        println!("{}", "❌ Nginx service is not running.".green());
+        say!("{}", "❌ Nginx service is not running.".green());
         return Ok(()); // Nginx is already running
     }
 
-    println!("Stopping Nginx service...");
+    say!("Stopping Nginx service...");
 
     let nginx_path = path::get_nginx_path()?;
     let nginx_exe = format!("{}/nginx.exe", &nginx_path);
@@ -65,7 +63,7 @@ pub fn stop() -> Result<(), Box<dyn std::error::Error>> {
         .wait()?;
 
     if output.success() {
-        println!("{}", "✔ Nginx service stopped successfully.".green());
+        say!("{}", "✔ Nginx service stopped successfully.".green());
     } else {
         eprintln!("Failed to stop Nginx service.");
         return Err("Failed to stop Nginx service".into());
@@ -75,20 +73,10 @@ pub fn stop() -> Result<(), Box<dyn std::error::Error>> {
 }
 
 pub fn reload() -> Result<(), Box<dyn std::error::Error>> {
-    println!("{}", "Reloading Nginx service...".yellow());
-    let nginx_path = path::get_nginx_path()?;
-    let nginx_exe = format!("{}/nginx.exe", &nginx_path);
-    let output = Command::new(&nginx_exe)
-        .arg("-s")
-        .arg("reload")
-        .spawn()?
-        .wait()?;
-    if output.success() {
-        println!("{}", "✔ Nginx service reloaded successfully.".green());
-    } else {
-        eprintln!("Failed to reload Nginx service.");
-        return Err("Failed to reload Nginx service".into());
-    }
+    say!("{}", "Reloading Nginx service...".yellow());
+    // Tests the configuration first, an invalid one is reported with nginx's own message
+    laracli::helpers::nginx::reload_nginx()?;
+    say!("{}", "✔ Nginx service reloaded successfully.".green());
 
     Ok(())
 }
\ No newline at end of file
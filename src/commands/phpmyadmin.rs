@@ -1,17 +1,23 @@
-use crate::helpers;
+use laracli::helpers;
+use laracli::helpers::archive::ExtractOptions;
+use laracli::say;
+use laracli::helpers::plan;
 use colored::Colorize;
-use std::fs;
+use std::path::Path;
+
+const PHPMYADMIN_DIR: &str = "phpMyAdmin-5.2.2-all-languages";
+const PHPMYADMIN_HOST: &str = "phpmyadmin";
 
 pub async fn enable_phpmyadmin() -> Result<(), Box<dyn std::error::Error>> {
-    let tools_dir = helpers::path::get_tools_path().unwrap();
+    let tools_dir = helpers::path::get_tools_path()?;
     //check if phpmyadmin is already installed
-    if !tools_dir.join("phpMyAdmin-5.2.2-all-languages").exists() {
-        println!("{}", "Installing PhpMyAdmin".yellow());
+    if !tools_dir.join(PHPMYADMIN_DIR).exists() {
+        say!("{}", "Installing PhpMyAdmin".yellow());
         //download  phpmyadmin
         let phpmyadmin_url =
             "https://files.phpmyadmin.net/phpMyAdmin/5.2.2/phpMyAdmin-5.2.2-all-languages.zip";
         let phpmyadmin_zip = "phpMyAdmin-5.2.2-all-languages.zip";
-        println!("{}", "Downloading PhpMyAdmin".yellow());
+        say!("{}", "Downloading PhpMyAdmin".yellow());
         helpers::download::download_with_progress_async(
             phpmyadmin_url,
             phpmyadmin_zip,
@@ -19,47 +25,158 @@ pub async fn enable_phpmyadmin() -> Result<(), Box<dyn std::error::Error>> {
             3,
         )
         .await
-        .expect("Failed to download PhpMyAdmin");
+        .map_err(|e| e as Box<dyn std::error::Error>)?;
 
         //unzip phpmyadmin
-        println!("{}", "Extracting PhpMyAdmin".yellow());
-        let phpmyadmin_file =
-            fs::File::open(phpmyadmin_zip).expect("Failed to open phpmyadmin zip file");
-        let mut zip =
-            zip::ZipArchive::new(phpmyadmin_file).expect("Failed to open phpmyadmin zip file");
-        zip.extract(&tools_dir)?;
-        fs::remove_file(phpmyadmin_zip).expect("Failed to remove phpmyadmin zip file");
-        println!("{}", "PhpMyAdmin installed successfully".green());
+        say!("{}", "Extracting PhpMyAdmin".yellow());
+        helpers::archive::extract_archive(
+            Path::new(phpmyadmin_zip),
+            &tools_dir,
+            &ExtractOptions {
+                progress: true,
+                label: "PhpMyAdmin".to_string(),
+            },
+        )?;
+        plan::remove_file(phpmyadmin_zip)?;
+        say!("{}", "PhpMyAdmin installed successfully".green());
+    }
+
+    let install_dir = tools_dir.join(PHPMYADMIN_DIR);
+    let install_path = install_dir.to_str().ok_or("Invalid phpMyAdmin path")?;
+
+    say!("{}", "Creating conf file for phpmyadmin".yellow());
+    helpers::nginx::create_nginx_config(install_path, Some(PHPMYADMIN_HOST))?;
+    say!("{}", "✅ config file created".yellow());
+
+    say!("{}", "Linking directory".yellow());
+    // Older versions recorded tools/phpmyadmin, which does not exist
+    let _ = helpers::config::remove_from_linked_paths(
+        tools_dir.join(PHPMYADMIN_HOST).to_str().ok_or("Invalid phpMyAdmin path")?,
+    );
+    helpers::config::add_to_linked_paths_with_name(install_path, PHPMYADMIN_HOST)?;
+
+    say!("{}", "Writing config.inc.php...".yellow());
+    write_phpmyadmin_config(&install_dir)?;
+    say!("{}", "✅ config.inc.php written".yellow());
+
+    crate::commands::nginx::reload()?;
+    say!("✅ ready to go visit http://{}.test", PHPMYADMIN_HOST);
+
+    Ok(())
+}
+
+// Write config.inc.php pointing at the managed MySQL server.
+// The blowfish secret of an existing config is kept so sessions survive a rerun.
+fn write_phpmyadmin_config(install_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let config_path = install_dir.join("config.inc.php");
+
+    let existing_secret = plan::read_to_string(&config_path)
+        .ok()
+        .and_then(|content| read_blowfish_secret(&content));
+    let secret = match existing_secret {
+        Some(secret) => secret,
+        None => generate_blowfish_secret()?,
+    };
+
+    plan::write(&config_path, generate_phpmyadmin_config(&secret))?;
+    Ok(())
+}
+
+fn read_blowfish_secret(content: &str) -> Option<String> {
+    let line = content
+        .lines()
+        .find(|line| line.trim_start().starts_with("$cfg['blowfish_secret']"))?;
+    let value = line.split('=').nth(1)?.trim().trim_end_matches(';').trim();
+    let secret = value.trim_matches('\'');
+    if secret.len() == 32 {
+        Some(secret.to_string())
+    } else {
+        None
     }
+}
 
-    println!("{}", "Creating conf file for phpmyadmin".yellow());
-    helpers::nginx::create_nginx_config(
-        &tools_dir
-            .join("phpMyAdmin-5.2.2-all-languages")
-            .to_str()
-            .unwrap(),
-        Some("phpmyadmin"),
+// phpMyAdmin expects a 32 byte secret
+fn generate_blowfish_secret() -> Result<String, Box<dyn std::error::Error>> {
+    const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).map_err(|e| format!("Failed to generate secret: {}", e))?;
+    Ok(bytes
+        .iter()
+        .map(|b| CHARSET[*b as usize % CHARSET.len()] as char)
+        .collect())
+}
+
+fn generate_phpmyadmin_config(secret: &str) -> String {
+    format!(
+        r#"<?php
+/**
+ * phpMyAdmin configuration generated by laracli.
+ * Rerunning `laracli enable phpmyadmin` rewrites this file.
+ */
+
+declare(strict_types=1);
+
+$cfg['blowfish_secret'] = '{}';
+
+$i = 0;
+$i++;
+$cfg['Servers'][$i]['auth_type'] = 'cookie';
+$cfg['Servers'][$i]['host'] = '{}';
+$cfg['Servers'][$i]['port'] = '{}';
+$cfg['Servers'][$i]['user'] = '{}';
+$cfg['Servers'][$i]['compress'] = false;
+$cfg['Servers'][$i]['AllowNoPassword'] = true;
+
+$cfg['UploadDir'] = '';
+$cfg['SaveDir'] = '';
+"#,
+        secret,
+        helpers::mysql::MYSQL_HOST,
+        helpers::mysql::MYSQL_PORT,
+        helpers::mysql::MYSQL_USER
     )
-    .expect("failed to create nginx config");
-    println!("{}", "✅ config file created".yellow());
-    println!("{}", "Linking directory".yellow());
-    helpers::config::add_to_linked_paths(tools_dir.join("phpmyadmin").to_str().unwrap());
-    println!("✅ Updated config with linked path: {}", tools_dir.join("phpmyadmin").to_str().unwrap());
-    println!("{}", "Adding config.php...");
-
-    //rename config.sample.inc.php to config.inc.php
-    let config_path = tools_dir.join("phpMyAdmin-5.2.2-all-languages").join("config.sample.inc.php");
-    let config_path_new = tools_dir.join("phpMyAdmin-5.2.2-all-languages").join("config.inc.php");
-    fs::rename(config_path, &config_path_new).expect("Failed to rename config.sample.inc.php to config.inc.php");
-    // $cfg['Servers'][$i]['AllowNoPassword'] = false; to true 
-    let config_file = fs::read_to_string(&config_path_new).expect("Failed to read config.php");
-    let config_file = config_file.replace("$cfg['Servers'][$i]['AllowNoPassword'] = false;", "$cfg['Servers'][$i]['AllowNoPassword'] = true;");
-    let config_file = config_file.replace("$cfg['Servers'][$i]['user'] = 'root';", "$cfg['Servers'][$i]['user'] = 'root';");
-    fs::write(&config_path_new, config_file).expect("Failed to write config.php");
-
-    crate::commands::nginx::reload().expect("Failed to reload nginx");
-    println!("✅ ready to go visit http://phpmyadmin.test");
+}
+
+pub fn disable_phpmyadmin() -> Result<(), Box<dyn std::error::Error>> {
+    let tools_dir = helpers::path::get_tools_path()?;
+    let install_dir = tools_dir.join(PHPMYADMIN_DIR);
 
+    say!("{}", "Disabling PhpMyAdmin".yellow());
+
+    match helpers::nginx::delete_nginx_config(install_dir.to_str().ok_or("Invalid phpMyAdmin path")?) {
+        Ok(()) => say!("✅ Nginx config deleted"),
+        Err(e) => say!("⚠️ Nginx config not removed: {}", e),
+    }
 
+    match helpers::hosts::remove_host_entry(PHPMYADMIN_HOST) {
+        Ok(()) => {}
+        Err(e) => say!("⚠️ Host entry not removed: {}", e),
+    }
+
+    // Older versions recorded tools/phpmyadmin instead of the real directory
+    for linked in [install_dir.clone(), tools_dir.join(PHPMYADMIN_HOST)] {
+        if helpers::config::remove_from_linked_paths(linked.to_str().ok_or("Invalid phpMyAdmin path")?).is_ok() {
+            say!("✅ Removed linked path: {}", linked.display());
+        }
+    }
+
+    crate::commands::nginx::reload()?;
+    say!("{}", "✅ PhpMyAdmin disabled".green());
     Ok(())
 }
+
+pub fn phpmyadmin_installed() -> bool {
+    helpers::path::get_tools_path()
+        .map(|tools_dir| tools_dir.join(PHPMYADMIN_DIR).exists())
+        .unwrap_or(false)
+}
+
+pub fn phpmyadmin_enabled() -> bool {
+    helpers::path::get_tools_path()
+        .ok()
+        .and_then(|tools_dir| {
+            helpers::nginx::site_config_path(tools_dir.join(PHPMYADMIN_DIR).to_str()?).ok()
+        })
+        .map(|config| config.exists())
+        .unwrap_or(false)
+}
@@ -0,0 +1,55 @@
+use crate::commands::link::{notify_daemon, report_rollback};
+use laracli::helpers;
+use laracli::helpers::ipc;
+use laracli::helpers::linking;
+use laracli::helpers::journal::{self, EventKind};
+use laracli::say;
+use laracli::error::LaracliError;
+use laracli::helpers::{output, plan};
+
+pub fn proxy(name: &str, target: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let name = site_name(name)?;
+    let target = helpers::nginx::normalize_proxy_target(target)?;
+
+    say!("Proxying {}.test to {}", name, target);
+    // Config, nginx config, then the daemon adds the host entry and reloads nginx;
+    // a failed step undoes the ones before it
+    linking::proxy_site(&name, &target, notify_daemon).inspect_err(report_rollback)?;
+    say!("✅ Updated config with proxy: {}", name);
+    say!("✅ Nginx config created");
+
+    say!("✅ http://{}.test now proxies to {}, WebSocket upgrades included.", name, target);
+    output::set("proxy", serde_json::json!({ "name": name, "target": target }));
+    say!("   Remove it with `laracli unlink {}`", name);
+    Ok(())
+}
+
+pub fn unproxy(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let name = site_name(name)?;
+    say!("Removing proxy {}.test", name);
+    helpers::config::remove_proxy(&name)?;
+    say!("✅ Removed proxy from config: {}", name);
+
+    if plan::exists(helpers::nginx::sites_enabled_dir()?.join(format!("{}.conf", name))) {
+        helpers::nginx::delete_proxy_config(&name)?;
+        say!("✅ Nginx config deleted");
+    }
+
+    let request = ipc::Request::RemoveSite {
+        path: None,
+        name: name.clone(),
+        subdomains: Vec::new(),
+    };
+    notify_daemon(request)?;
+    journal::record(EventKind::SiteUnlinked, &name, "proxy");
+    output::set("proxy", serde_json::json!({ "name": name }));
+
+    Ok(())
+}
+
+// `vite.test`, `Vite` and `vite` all name the same site
+fn site_name(name: &str) -> Result<String, LaracliError> {
+    let name = name.trim_end_matches(".test").to_lowercase();
+    helpers::hosts::validate_host_name(&name)?;
+    Ok(name)
+}
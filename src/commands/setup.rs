@@ -1,7 +1,7 @@
 use colored::Colorize;
 use futures_util::StreamExt;
 use indicatif::{ProgressBar, ProgressStyle};
-use laracli::helpers::{self, config, nginx};
+use laracli::helpers::{self, archive::ExtractOptions};
 use std::fs::File;
 use std::fs::OpenOptions;
 use std::io::Write;
@@ -14,11 +14,13 @@ use windows::Win32::UI::WindowsAndMessaging::{
 };
 use winreg::RegKey;
 use winreg::enums::*;
-use zip::ZipArchive;
+use laracli::say;
+use laracli::error::LaracliError;
+use laracli::helpers::plan::{self, Step};
 
 pub fn setup_permissions() -> Result<(), Box<dyn std::error::Error>> {
     // Define resources: (resource_name, binary_path, base_path_function, directories_to_grant)
-    let resources = vec![
+    let resources = [
         (
             "laracli",
             "laracli.exe",
@@ -28,13 +30,13 @@ pub fn setup_permissions() -> Result<(), Box<dyn std::error::Error>> {
         (
             "nginx",
             "nginx.exe",
-            Some(Box::new(|| helpers::path::get_nginx_path())),
+            Some(Box::new(helpers::path::get_nginx_path)),
             vec![("logs", "(M)"), ("conf", "(M)")],
         ),
         (
             "mysql",
             "bin/mysqld.exe",
-            Some(Box::new(|| helpers::path::get_mysql_path())),
+            Some(Box::new(helpers::path::get_mysql_path)),
             vec![("data", "(M)"), ("my.ini", "(M)")],
         ),
         (
@@ -48,8 +50,8 @@ pub fn setup_permissions() -> Result<(), Box<dyn std::error::Error>> {
     ];
 
     // Ensure we're running with admin privileges
-    if !is_elevated() {
-        println!(
+    if !plan::is_dry_run() && !is_elevated() {
+        say!(
             "{}",
             "This command requires administrative privileges. Please run as administrator.".green()
         );
@@ -61,7 +63,7 @@ pub fn setup_permissions() -> Result<(), Box<dyn std::error::Error>> {
     let exe_dir = exe_path
         .parent()
         .ok_or("Could not determine executable directory")?;
-    println!("Using executable directory: {:?}", exe_dir);
+    say!("Using executable directory: {:?}", exe_dir);
 
     // Configure permissions for each resource
     for (resource_name, binary_path, base_path_fn, directories) in resources.iter() {
@@ -72,34 +74,38 @@ pub fn setup_permissions() -> Result<(), Box<dyn std::error::Error>> {
             exe_dir.join(binary_path)
         };
 
-        if !binary_full_path.exists() {
-            println!("Binary not found: {:?}", binary_full_path);
+        // setup downloads the tools first, a dry run only plans that
+        if !binary_full_path.exists() && !plan::is_dry_run() {
+            say!("Binary not found: {:?}", binary_full_path);
             return Err(format!("Binary {} not found", binary_path).into());
         }
 
         // Grant Users group read/execute permissions on the binary
-        println!("Setting permissions for {}", binary_full_path.display());
-        let icacls_output = Command::new("icacls")
-            .args(&[
-                binary_full_path.to_str().ok_or("Invalid path")?,
-                "/grant",
-                "*S-1-5-32-545:(RX)",
-                "/T",
-            ])
-            .output()?;
-        if !icacls_output.status.success() {
-            let stderr = String::from_utf8_lossy(&icacls_output.stderr);
-            println!(
-                "Failed to set permissions for {}: {}",
-                binary_full_path.display(),
-                stderr
-            );
-            return Err(format!(
-                "Failed to set permissions for {}: {}",
-                binary_full_path.display(),
-                stderr
-            )
-            .into());
+        say!("Setting permissions for {}", binary_full_path.display());
+        let grant = format!("icacls {} /grant *S-1-5-32-545:(RX) /T", binary_full_path.display());
+        if !plan::dry_run(Step::Run { command: grant }) {
+            let icacls_output = Command::new("icacls")
+                .args([
+                    binary_full_path.to_str().ok_or("Invalid path")?,
+                    "/grant",
+                    "*S-1-5-32-545:(RX)",
+                    "/T",
+                ])
+                .output()?;
+            if !icacls_output.status.success() {
+                let stderr = String::from_utf8_lossy(&icacls_output.stderr);
+                say!(
+                    "Failed to set permissions for {}: {}",
+                    binary_full_path.display(),
+                    stderr
+                );
+                return Err(format!(
+                    "Failed to set permissions for {}: {}",
+                    binary_full_path.display(),
+                    stderr
+                )
+                .into());
+            }
         }
 
         // Grant permissions on directories if base_path_fn exists
@@ -107,9 +113,13 @@ pub fn setup_permissions() -> Result<(), Box<dyn std::error::Error>> {
             let base_path = get_path()?;
             for (dir, perm) in directories {
                 let dir_path = Path::new(&base_path).join(dir);
+                let grant = format!("icacls {} /grant *S-1-5-32-545:{} /T", dir_path.display(), perm);
+                if plan::dry_run(Step::Run { command: grant }) {
+                    continue;
+                }
                 if dir_path.exists() {
                     let icacls_dir_output = Command::new("icacls")
-                        .args(&[
+                        .args([
                             dir_path.to_str().ok_or("Invalid directory path")?,
                             "/grant",
                             &format!("*S-1-5-32-545:{}", perm),
@@ -118,7 +128,7 @@ pub fn setup_permissions() -> Result<(), Box<dyn std::error::Error>> {
                         .output()?;
                     if !icacls_dir_output.status.success() {
                         let stderr = String::from_utf8_lossy(&icacls_dir_output.stderr);
-                        println!(
+                        say!(
                             "Failed to set permissions for {}: {}",
                             dir_path.display(),
                             stderr
@@ -131,7 +141,7 @@ pub fn setup_permissions() -> Result<(), Box<dyn std::error::Error>> {
                         .into());
                     }
                 } else {
-                    println!(
+                    say!(
                         "{}",
                         format!(
                             "⚠️ Directory {} does not exist, skipping permission configuration",
@@ -143,62 +153,68 @@ pub fn setup_permissions() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
 
-        println!("Successfully configured permissions for {}", resource_name);
+        say!("Successfully configured permissions for {}", resource_name);
     }
 
     // Create default config if it doesn't exist
     let config_path = Path::new(r"C:\laracli\config.json");
-    if !config_path.exists() {
-        fs::create_dir_all(config_path.parent().unwrap())?;
+    if !plan::exists(config_path) {
+        plan::create_dir_all(config_path.parent().unwrap())?;
         let default_config = r#"{
             "watched_paths": [],
             "linked_paths": []
         }"#;
-        fs::write(config_path, default_config)?;
-        println!("Created default config at {:?}", config_path);
+        plan::write(config_path, default_config)?;
+        say!("Created default config at {:?}", config_path);
     }
 
     Ok(())
 }
 
 pub fn setup_services() -> Result<(), Box<dyn std::error::Error>> {
-    let services = vec![
-        ("laracli", "laracli-service.exe"),
-        ("laracli_config", "laracli-service-config.exe"),
-    ];
+    let services = [("laracli", "laracli-service.exe")];
 
     // Ensure we're running with admin privileges
-    if !is_elevated() {
-        println!(
+    if !plan::is_dry_run() && !is_elevated() {
+        say!(
             "{}",
             "This command requires administrative privileges. Please run as administrator.".green()
         );
         return Err("Administrative privileges required".into());
     }
 
+    remove_legacy_config_service()?;
+
     // Get the directory of the current executable
     let exe_path = std::env::current_exe()?;
     let exe_dir = exe_path
         .parent()
         .ok_or("Could not determine executable directory")?;
-    println!("Using executable directory: {:?}", exe_dir);
+    say!("Using executable directory: {:?}", exe_dir);
 
     // Verify binaries exist in the executable directory
     for (_, binary_name) in services.iter() {
         let binary_path = exe_dir.join(binary_name);
         if !binary_path.exists() {
-            println!("Binary not found: {:?}", binary_path);
+            say!("Binary not found: {:?}", binary_path);
             return Err(format!("Binary {} not found in {:?}", binary_name, exe_dir).into());
         }
     }
 
     //check if the services are already installed
     for (service_name, binary_name) in services.iter() {
-        let output = Command::new("sc").args(&["query", service_name]).output()?;
+        let output = Command::new("sc").args(["query", service_name]).output()?;
         if output.status.success() {
-            println!("Service {} is already installed", service_name);
+            say!("Service {} is already installed", service_name);
             continue;
         } else {
+            if plan::dry_run(Step::Service {
+                name: format!("the {} service", service_name),
+                action: "Install and start".to_string(),
+            }) {
+                continue;
+            }
+
             // install the service
             // Construct the full path for the service binary
             let binary_path = exe_dir.join(binary_name).canonicalize()?;
@@ -214,14 +230,14 @@ pub fn setup_services() -> Result<(), Box<dyn std::error::Error>> {
                 "start=",
                 "auto",
             ];
-            println!("Executing sc command: sc {}", sc_args.join(" "));
+            say!("Executing sc command: sc {}", sc_args.join(" "));
 
             // Install service
             let install_output = Command::new("sc").args(&sc_args).output()?;
 
             if !install_output.status.success() {
                 let stderr = String::from_utf8_lossy(&install_output.stderr);
-                println!("Failed to install {} service: {}", service_name, stderr);
+                say!("Failed to install {} service: {}", service_name, stderr);
                 return Err(
                     format!("Failed to install {} service: {}", service_name, stderr).into(),
                 );
@@ -229,7 +245,7 @@ pub fn setup_services() -> Result<(), Box<dyn std::error::Error>> {
 
             // Set service permissions
             let perm_output = Command::new("sc")
-            .args(&[
+            .args([
                 "sdset",
                 service_name,
                 "D:(A;;CCLCSWRPWPDTLOCRRC;;;SY)(A;;CCDCLCSWRPWPDTLOCRSDRCWDWO;;;BA)(A;;CCLCSWLOCRRC;;;IU)(A;;CCLCSWLOCRRC;;;SU)",
@@ -237,7 +253,7 @@ pub fn setup_services() -> Result<(), Box<dyn std::error::Error>> {
             .output()?;
 
             if !perm_output.status.success() {
-                println!(
+                say!(
                     "Failed to set permissions for {} service: {}",
                     service_name,
                     String::from_utf8_lossy(&perm_output.stderr)
@@ -250,11 +266,11 @@ pub fn setup_services() -> Result<(), Box<dyn std::error::Error>> {
             // Grant permissions to hosts file
             let hosts_path = r"C:\Windows\System32\drivers\etc\hosts";
             let icacls_output = Command::new("icacls")
-                .args(&[hosts_path, "/grant", "*S-1-5-19:F", "/T"])
+                .args([hosts_path, "/grant", "*S-1-5-19:F", "/T"])
                 .output()?;
 
             if !icacls_output.status.success() {
-                println!(
+                say!(
                     "Failed to set hosts file permissions: {}",
                     String::from_utf8_lossy(&icacls_output.stderr)
                 );
@@ -262,10 +278,10 @@ pub fn setup_services() -> Result<(), Box<dyn std::error::Error>> {
             }
 
             // Start the service
-            let start_output = Command::new("sc").args(&["start", service_name]).output()?;
+            let start_output = Command::new("sc").args(["start", service_name]).output()?;
 
             if !start_output.status.success() {
-                println!(
+                say!(
                     "Failed to start {} service: {}",
                     service_name,
                     String::from_utf8_lossy(&start_output.stderr)
@@ -273,7 +289,7 @@ pub fn setup_services() -> Result<(), Box<dyn std::error::Error>> {
                 return Err(format!("Failed to start {} service", service_name).into());
             }
 
-            println!(
+            say!(
                 "Successfully installed and started {} service",
                 service_name
             );
@@ -282,14 +298,14 @@ pub fn setup_services() -> Result<(), Box<dyn std::error::Error>> {
 
     // Create default config if it doesn't exist
     let config_path = Path::new(r"C:\laracli\config.json");
-    if !config_path.exists() {
-        fs::create_dir_all(config_path.parent().unwrap())?;
+    if !plan::exists(config_path) {
+        plan::create_dir_all(config_path.parent().unwrap())?;
         let default_config = r#"{
             "watched_paths": [],
             "linked_paths": []
         }"#;
-        fs::write(config_path, default_config)?;
-        println!("Created default config at {:?}", config_path);
+        plan::write(config_path, default_config)?;
+        say!("Created default config at {:?}", config_path);
     }
 
     Ok(())
@@ -301,6 +317,10 @@ pub async fn download_with_progress_async(
     label: &str,
     max_retries: usize,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    if plan::dry_run(Step::Download { url: url.to_string(), path: out_path.into() }) {
+        return Ok(());
+    }
+
     // Create a more robust HTTP client
     let client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(300)) // 5 minute timeout
@@ -311,7 +331,7 @@ pub async fn download_with_progress_async(
         .build()?;
 
     for attempt in 1..=max_retries {
-        println!(
+        say!(
             "{}",
             format!(
                 "Downloading {} (Attempt {}/{})",
@@ -322,11 +342,12 @@ pub async fn download_with_progress_async(
 
         // Check if partial file exists for resume
         let mut resume_from = 0u64;
-        if attempt > 1 && std::path::Path::new(out_path).exists() {
-            if let Ok(metadata) = std::fs::metadata(out_path) {
-                resume_from = metadata.len();
-                println!("Resuming download from {} bytes", resume_from);
-            }
+        if attempt > 1
+            && std::path::Path::new(out_path).exists()
+            && let Ok(metadata) = std::fs::metadata(out_path)
+        {
+            resume_from = metadata.len();
+            say!("Resuming download from {} bytes", resume_from);
         }
 
         // Build request with range header for resume
@@ -338,7 +359,7 @@ pub async fn download_with_progress_async(
         let res = match request.send().await {
             Ok(response) => response,
             Err(e) => {
-                println!("❌ Request failed: {}", e);
+                say!("❌ Request failed: {}", e);
                 if attempt == max_retries {
                     return Err(format!(
                         "Failed to GET from '{}' after {} attempts: {}",
@@ -352,7 +373,7 @@ pub async fn download_with_progress_async(
         };
 
         if !res.status().is_success() && res.status().as_u16() != 206 {
-            println!("❌ HTTP error: {}", res.status());
+            say!("❌ HTTP error: {}", res.status());
             if attempt == max_retries {
                 return Err(format!(
                     "HTTP error {}: {}",
@@ -396,7 +417,7 @@ pub async fn download_with_progress_async(
             match OpenOptions::new().create(true).append(true).open(out_path) {
                 Ok(f) => f,
                 Err(e) => {
-                    println!("❌ Failed to open file for append '{}': {}", out_path, e);
+                    say!("❌ Failed to open file for append '{}': {}", out_path, e);
                     if attempt == max_retries {
                         return Err(format!("Failed to open file '{}': {}", out_path, e).into());
                     }
@@ -408,7 +429,7 @@ pub async fn download_with_progress_async(
             match File::create(out_path) {
                 Ok(f) => f,
                 Err(e) => {
-                    println!("❌ Failed to create file '{}': {}", out_path, e);
+                    say!("❌ Failed to create file '{}': {}", out_path, e);
                     if attempt == max_retries {
                         return Err(format!("Failed to create file '{}': {}", out_path, e).into());
                     }
@@ -428,7 +449,7 @@ pub async fn download_with_progress_async(
             match item {
                 Ok(chunk) => {
                     if let Err(e) = file.write_all(&chunk) {
-                        println!("❌ Error writing to file: {}", e);
+                        say!("❌ Error writing to file: {}", e);
                         download_success = false;
                         break;
                     }
@@ -436,11 +457,11 @@ pub async fn download_with_progress_async(
                     pb.set_position(downloaded);
 
                     // Flush every 10MB or every 5 seconds
-                    if downloaded % (10 * 1024 * 1024) == 0
+                    if downloaded.is_multiple_of(10 * 1024 * 1024)
                         || last_progress.elapsed() > Duration::from_secs(5)
                     {
                         if let Err(e) = file.flush() {
-                            println!("❌ Error flushing file: {}", e);
+                            say!("❌ Error flushing file: {}", e);
                             download_success = false;
                             break;
                         }
@@ -448,7 +469,7 @@ pub async fn download_with_progress_async(
                     }
                 }
                 Err(e) => {
-                    println!("❌ Error while downloading chunk: {}", e);
+                    say!("❌ Error while downloading chunk: {}", e);
                     download_success = false;
                     break;
                 }
@@ -457,17 +478,17 @@ pub async fn download_with_progress_async(
 
         // Final flush
         if let Err(e) = file.flush() {
-            println!("❌ Error during final flush: {}", e);
+            say!("❌ Error during final flush: {}", e);
             download_success = false;
         }
 
         if download_success && (total_size == 0 || downloaded >= total_size) {
             pb.finish_with_message(format!("✅ {} downloaded successfully", label));
-            println!("✅ {} downloaded ({} bytes)", label, downloaded);
+            say!("✅ {} downloaded ({} bytes)", label, downloaded);
             return Ok(());
         } else {
             pb.finish_with_message(format!("❌ {} download failed", label));
-            println!(
+            say!(
                 "❌ Download failed. Expected: {} bytes, Downloaded: {} bytes",
                 total_size, downloaded
             );
@@ -475,7 +496,7 @@ pub async fn download_with_progress_async(
             if attempt == max_retries {
                 // Clean up partial file only on final failure
                 if let Err(e) = std::fs::remove_file(out_path) {
-                    println!("Warning: Failed to remove partial file: {}", e);
+                    say!("Warning: Failed to remove partial file: {}", e);
                 }
                 return Err(format!(
                     "Download incomplete after {} attempts. Last attempt downloaded {} of {} bytes",
@@ -484,7 +505,7 @@ pub async fn download_with_progress_async(
                 .into());
             }
 
-            println!("Retrying in 10 seconds...");
+            say!("Retrying in 10 seconds...");
             tokio::time::sleep(Duration::from_secs(10)).await;
         }
     }
@@ -496,31 +517,41 @@ pub async fn setup_tools() -> Result<(), Box<dyn std::error::Error>> {
     let tools_dir = helpers::path::get_tools_path()?;
 
     // Create tools directory if it doesn't exist
-    fs::create_dir_all(&tools_dir)?;
+    plan::create_dir_all(&tools_dir)?;
 
     // --- Download and extract Nginx ---
     let nginx_url = "http://nginx.org/download/nginx-1.23.3.zip";
     let nginx_zip = "nginx-1.23.3.zip";
     download_with_progress_async(nginx_url, nginx_zip, "Nginx", 3).await?;
 
-    println!("{}", "Extracting Nginx".yellow());
-    let nginx_file = fs::File::open(nginx_zip)?;
-    let mut nginx_archive = ZipArchive::new(nginx_file)?;
-    nginx_archive.extract(&tools_dir)?;
-    fs::remove_file(nginx_zip)?;
-    println!("{}", "✅ Nginx extracted successfully".green());
-    println!("");
+    say!("{}", "Extracting Nginx".yellow());
+    helpers::archive::extract_archive(
+        Path::new(nginx_zip),
+        &tools_dir,
+        &ExtractOptions {
+            progress: true,
+            label: "Nginx".to_string(),
+        },
+    )?;
+    plan::remove_file(nginx_zip)?;
+    say!("{}", "✅ Nginx extracted successfully".green());
+    say!("");
 
     // --- Download and extract Php ---
     let php_url = "https://repos.zend.com/zendphp/windows/zendphp-8.3.22-nts-Win32-vs16-x64.zip";
     let php_zip = "php-8.3.22-nts-Win32-vs16-x64.zip";
-    println!("{}", "Downloading PHP ...".yellow());
+    say!("{}", "Downloading PHP ...".yellow());
     download_with_progress_async(php_url, php_zip, "PHP", 3).await?;
-    println!("{}", "Extracting PHP".yellow());
-    let php_file = fs::File::open(php_zip)?;
-    let mut php_archive = ZipArchive::new(php_file)?;
-    php_archive.extract(&tools_dir.join(&php_zip.replace(".zip", "")))?;
-    fs::remove_file(php_zip)?;
+    say!("{}", "Extracting PHP".yellow());
+    helpers::archive::extract_archive(
+        Path::new(php_zip),
+        &tools_dir.join(php_zip.replace(".zip", "")),
+        &ExtractOptions {
+            progress: true,
+            label: "PHP".to_string(),
+        },
+    )?;
+    plan::remove_file(php_zip)?;
     // Rename php.ini-development to php.ini
     let php_ini_development = tools_dir
         .join("php-8.3.22-nts-Win32-vs16-x64")
@@ -528,75 +559,89 @@ pub async fn setup_tools() -> Result<(), Box<dyn std::error::Error>> {
     let php_ini = tools_dir
         .join("php-8.3.22-nts-Win32-vs16-x64")
         .join("php.ini");
-    fs::rename(php_ini_development, php_ini)?;
-    println!("{}", "✅ PHP extracted successfully".green());
-    println!("");
+    if !plan::dry_run(Step::RenameFile { from: php_ini_development.clone(), to: php_ini.clone() }) {
+        fs::rename(php_ini_development, php_ini)?;
+    }
+    say!("{}", "✅ PHP extracted successfully".green());
+    say!("");
 
     //--- Download and extract MySQL ---
     let mysql_url = "https://cdn.mysql.com//Downloads/MySQL-8.4/mysql-8.4.5-winx64.zip";
     let mysql_zip = "mysql-8.4.5-winx64_2.zip";
-    println!(
+    say!(
         "{}",
         "Downloading MySQL (approx. 247 MB, may take a few minutes)...".yellow()
     );
     download_with_progress_async(mysql_url, mysql_zip, "MySQL", 7).await?;
 
-    println!("{}", "Extracting MySQL".yellow());
-    let mysql_file = fs::File::open(mysql_zip)?;
-    let mut mysql_archive = ZipArchive::new(mysql_file)?;
-    mysql_archive.extract(&tools_dir)?;
-    fs::remove_file(mysql_zip)?;
-    println!("{}", "✅ MySQL extracted successfully".green());
-    println!("");
+    say!("{}", "Extracting MySQL".yellow());
+    helpers::archive::extract_archive(
+        Path::new(mysql_zip),
+        &tools_dir,
+        &ExtractOptions {
+            progress: true,
+            label: "MySQL".to_string(),
+        },
+    )?;
+    plan::remove_file(mysql_zip)?;
+    say!("{}", "✅ MySQL extracted successfully".green());
+    say!("");
 
     // --- Create Global Nginx Config ---
-    println!("{}", "Creating config files".yellow());
+    say!("{}", "Creating config files".yellow());
     match helpers::nginx::create_global_nginx_config() {
-        Ok(_) => println!("{}", "✅ Global Nginx config created".green()),
-        Err(e) => println!(
+        Ok(_) => say!("{}", "✅ Global Nginx config created".green()),
+        Err(e) => say!(
             "{}",
             format!("❌ Error creating global Nginx config: {}", e).red()
         ),
     }
 
     // --- Create my.ini for MySQL ---
-    println!("{}", "Creating my.ini".yellow());
-    helpers::mysql::create_my_ini_file();
-    println!("{}", "✅ my.ini created".green());
+    say!("{}", "Creating my.ini".yellow());
+    helpers::mysql::create_my_ini_file()?;
+    say!("{}", "✅ my.ini created".green());
 
     // --- Initialize MySQL Data Directory ---
-    println!("{}", "Creating MySQL data directory".yellow());
-    let mysql_path = helpers::path::get_mysql_path().unwrap();
+    say!("{}", "Creating MySQL data directory".yellow());
+    let mysql_path = helpers::path::get_mysql_path()?;
     let mysqld_path = Path::new(&mysql_path).join("bin").join("mysqld.exe");
 
-    let output = Command::new(&mysqld_path)
-        .arg("--initialize-insecure")
-        .arg("--basedir")
-        .arg(&mysql_path)
-        .arg("--datadir")
-        .arg(Path::new(&mysql_path).join("data"))
-        .output();
-
-    let mysql_data_dir = Path::new(&mysql_path).join("data");
-
-    if mysql_data_dir.exists() {
-        println!("{}", "✅ MySQL data directory created".green());
-    } else {
-        println!("{}", "❌ Error creating MySQL data directory".red());
+    let initialize = format!("{} --initialize-insecure", mysqld_path.display());
+    if !plan::dry_run(Step::Run { command: initialize }) {
+        let output = Command::new(&mysqld_path)
+            .arg("--initialize-insecure")
+            .arg("--basedir")
+            .arg(&mysql_path)
+            .arg("--datadir")
+            .arg(Path::new(&mysql_path).join("data"))
+            .output();
+
+        let mysql_data_dir = Path::new(&mysql_path).join("data");
+
+        match output {
+            Ok(_) if mysql_data_dir.exists() => say!("{}", "✅ MySQL data directory created".green()),
+            Ok(output) => say!(
+                "{} {}",
+                "❌ Error creating MySQL data directory:".red(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            Err(e) => say!("{} {}", "❌ Error creating MySQL data directory:".red(), e),
+        }
     }
 
     Ok(())
 }
 
 pub fn add_exe_to_path() -> Result<(), Box<dyn std::error::Error>> {
-    println!("{}", "Adding current executable to PATH".yellow());
-    let new_path = helpers::path::get_current_exe_dir().unwrap();
+    say!("{}", "Adding current executable to PATH".yellow());
+    let new_path = helpers::path::get_current_exe_dir()?;
 
     // Open the user environment variables
     let hkcu = RegKey::predef(HKEY_CURRENT_USER);
     let env = hkcu
         .open_subkey_with_flags("Environment", KEY_READ | KEY_WRITE)
-        .expect("Failed to open Environment key");
+        .map_err(|e| LaracliError::from_io("open the Environment registry key", e))?;
 
     // Read the existing PATH value
     let current_path: String = env.get_value("Path").unwrap_or_default();
@@ -607,15 +652,19 @@ pub fn add_exe_to_path() -> Result<(), Box<dyn std::error::Error>> {
         .contains(&new_path.to_lowercase())
     {
         let updated_path = format!("{};{}", current_path, new_path);
-        env.set_value("Path", &updated_path)?;
-        println!("{}", "✅ Current executable added to PATH".green());
+        if !plan::dry_run(Step::Other {
+            description: format!("Add {} to the user PATH", new_path),
+        }) {
+            env.set_value("Path", &updated_path)?;
+        }
+        say!("{}", "✅ Current executable added to PATH".green());
     } else {
-        println!("{}", "ℹ️ Path already contains the directory.".blue());
+        say!("{}", "ℹ️ Path already contains the directory.".blue());
     }
 
     notify_environment_change();
 
-    println!(
+    say!(
         "{}",
         r#"🚀 You are ready to star type "laracli start-dev"  "#
             .green()
@@ -625,15 +674,15 @@ pub fn add_exe_to_path() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-fn is_elevated() -> bool {
+pub fn is_elevated() -> bool {
     Command::new("net")
-        .args(&["session"])
+        .args(["session"])
         .output()
         .map(|output| output.status.success())
         .unwrap_or(false)
 }
 
-fn notify_environment_change() {
+pub fn notify_environment_change() {
     unsafe {
         SendMessageTimeoutW(
             HWND_BROADCAST,
@@ -646,3 +695,30 @@ fn notify_environment_change() {
         );
     }
 }
+
+// Older versions ran linked paths in a separate `laracli_config` service, the daemon now does it
+fn remove_legacy_config_service() -> Result<(), Box<dyn std::error::Error>> {
+    let service_name = "laracli_config";
+    let output = Command::new("sc").args(["query", service_name]).output()?;
+    if !output.status.success() {
+        return Ok(());
+    }
+
+    if plan::dry_run(Step::Service {
+        name: format!("the legacy {} service", service_name),
+        action: "Remove".to_string(),
+    }) {
+        return Ok(());
+    }
+    say!("Removing legacy {} service", service_name);
+    let _ = Command::new("sc").args(["stop", service_name]).output()?;
+    let delete_output = Command::new("sc").args(["delete", service_name]).output()?;
+    if !delete_output.status.success() {
+        say!(
+            "Failed to remove legacy {} service: {}",
+            service_name,
+            String::from_utf8_lossy(&delete_output.stderr)
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use colored::Colorize;
+use laracli::helpers::ipc;
+use laracli::error::LaracliError;
+use laracli::helpers::output;
+use laracli::say;
+
+/// Query the laracli daemon over its control endpoint
+pub fn status() -> Result<(), Box<dyn std::error::Error>> {
+    match ipc::send(ipc::DAEMON_ENDPOINT, &ipc::Request::Status) {
+        Ok(response) if response.ok => {
+            say!("{} {}", "✔".green(), response.message);
+            output::set("daemon", &response.data);
+            if let Some(tasks) = response.data.get("tasks").and_then(|tasks| tasks.as_object()) {
+                for (name, status) in tasks {
+                    say!("  {} {}", format!("{}:", name).cyan(), status);
+                }
+            }
+            Ok(())
+        }
+        Ok(response) => {
+            say!("{} laracli: {}", "❌".red(), response.message);
+            Err("The laracli daemon reported an error".into())
+        }
+        Err(e) => Err(LaracliError::ServiceUnavailable(format!("laracli is not responding: {}", e)).into()),
+    }
+}
@@ -0,0 +1,51 @@
+use crate::commands::link::notify_daemon;
+use laracli::helpers;
+use laracli::helpers::ipc;
+use laracli::say;
+use laracli::error::LaracliError;
+
+// A single DNS label: letters, digits and inner hyphens
+fn validate_subdomain(subdomain: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if !helpers::hosts::is_dns_label(subdomain) {
+        return Err(LaracliError::InvalidArgument(format!("Invalid subdomain: {}", subdomain)).into());
+    }
+    Ok(())
+}
+
+pub fn add(site: &str, subdomain: &str) -> Result<(), Box<dyn std::error::Error>> {
+    validate_subdomain(subdomain)?;
+    let subdomain = subdomain.to_lowercase();
+    let path = helpers::config::add_subdomain(site, &subdomain)?;
+    let host_name = format!("{}.{}", subdomain, site);
+
+    // server_name lists the registered subdomains
+    helpers::nginx::create_nginx_config(&path, Some(site))?;
+    say!("✅ Nginx config updated");
+
+    notify_daemon(ipc::Request::AddSite {
+        path: Some(path.clone()),
+        name: host_name.clone(),
+    })?;
+
+    say!("✅ {}.test now serves {}", host_name, path);
+    Ok(())
+}
+
+pub fn remove(site: &str, subdomain: &str) -> Result<(), Box<dyn std::error::Error>> {
+    validate_subdomain(subdomain)?;
+    let subdomain = subdomain.to_lowercase();
+    let path = helpers::config::remove_subdomain(site, &subdomain)?;
+    let host_name = format!("{}.{}", subdomain, site);
+
+    helpers::nginx::create_nginx_config(&path, Some(site))?;
+    say!("✅ Nginx config updated");
+
+    notify_daemon(ipc::Request::RemoveSite {
+        path: Some(path.clone()),
+        name: host_name.clone(),
+        subdomains: Vec::new(),
+    })?;
+
+    say!("✅ {}.test removed", host_name);
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use colored::Colorize;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use laracli::helpers::journal::{self, EventKind};
+use laracli::helpers::process::DevProcess;
+use laracli::helpers::{self, output};
+use laracli::say;
+
+// Minimum time between two restarts of the same process
+const RESTART_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Restart the supervised dev processes when they stop, in the session of the user
+/// who ran `start-dev --supervise`, until `stop-dev` or Ctrl+C
+pub async fn supervise() -> Result<(), Box<dyn std::error::Error>> {
+    if !output::is_text() {
+        return Err("--supervise only works with text output".into());
+    }
+
+    say!("{}", "Supervising PHP, Nginx and MySQL, press Ctrl+C or run `laracli stop-dev` to stop.".yellow());
+    let mut last_restart: HashMap<DevProcess, Instant> = HashMap::new();
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {
+                helpers::config::set_supervised_processes(&[])?;
+                say!("Stopped supervising, the processes keep running.");
+                return Ok(());
+            }
+            _ = tokio::time::sleep(Duration::from_secs(2)) => {}
+        }
+
+        // `stop-dev` clears the list before it stops the processes
+        let supervised = helpers::config::read_config()?.supervise;
+        if supervised.is_empty() {
+            say!("Stopped supervising.");
+            return Ok(());
+        }
+
+        for process in DevProcess::ALL {
+            if !supervised.contains(process.name()) || process.is_running() {
+                continue;
+            }
+            if last_restart.get(&process).is_some_and(|last| last.elapsed() < RESTART_BACKOFF) {
+                continue;
+            }
+
+            last_restart.insert(process, Instant::now());
+            match process.spawn() {
+                Ok(child) => {
+                    say!("{} {} stopped, restarted it (PID {})", "↻".yellow(), process.name(), child.id());
+                    journal::record(EventKind::ProcessRestarted, process.name(), &format!("PID {}", child.id()));
+                }
+                Err(e) => say!("{} Failed to restart {}: {}", "❌".red(), process.name(), e),
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use colored::Colorize;
+use laracli::utils::elevate;
+use laracli::helpers::ipc;
+use laracli::helpers::reconcile::{self, Action, Outcome};
+use laracli::say;
+use laracli::helpers::output;
+
+/// Converge nginx and hosts with config.json, through the daemon when it is running
+pub fn sync(dry_run: bool) -> Result<(), Box<dyn std::error::Error>> {
+    match ipc::send(ipc::DAEMON_ENDPOINT, &ipc::Request::Sync { dry_run }) {
+        Ok(response) => {
+            let entries = response.data.get("actions").cloned().unwrap_or_default();
+            if dry_run {
+                print_plan(&serde_json::from_value::<Vec<Action>>(entries).unwrap_or_default());
+            } else {
+                print_outcomes(&serde_json::from_value::<Vec<Outcome>>(entries).unwrap_or_default());
+            }
+            if response.ok {
+                Ok(())
+            } else {
+                Err(response.message.into())
+            }
+        }
+        Err(e) => {
+            say!("⚠️ {}, syncing without the service", e);
+            sync_locally(dry_run)
+        }
+    }
+}
+
+fn sync_locally(dry_run: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if !dry_run && !elevate::is_elevated() {
+        say!("🔒 Elevation required to modify hosts file. Requesting UAC permission...");
+        elevate::run_as_admin()?;
+        return Ok(());
+    }
+
+    let actions = reconcile::plan_current()?;
+    if dry_run {
+        print_plan(&actions);
+        return Ok(());
+    }
+
+    let outcomes = reconcile::apply(&actions);
+    print_outcomes(&outcomes);
+    if outcomes.iter().any(|outcome| outcome.error.is_some()) {
+        return Err("Some changes failed".into());
+    }
+    Ok(())
+}
+
+fn print_plan(actions: &[Action]) {
+    output::set("actions", actions);
+    if actions.is_empty() {
+        say!("{}", "✔ Everything is in sync.".green());
+        return;
+    }
+    say!("Planned changes:");
+    for action in actions {
+        say!("  - {}", action);
+    }
+}
+
+fn print_outcomes(outcomes: &[Outcome]) {
+    output::set("outcomes", outcomes);
+    if outcomes.is_empty() {
+        say!("{}", "✔ Everything is in sync.".green());
+        return;
+    }
+    for outcome in outcomes {
+        match &outcome.error {
+            Some(e) => say!("{} {}: {}", "❌".red(), outcome.action, e),
+            None => say!("{} {}", "✔".green(), outcome.action),
+        }
+    }
+}
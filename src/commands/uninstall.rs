@@ -0,0 +1,205 @@
+use colored::Colorize;
+use laracli::helpers;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::Command;
+use winreg::RegKey;
+use winreg::enums::*;
+
+use crate::commands::setup::{is_elevated, notify_environment_change};
+use laracli::say;
+
+const HOSTS_PATH: &str = r"C:\Windows\System32\drivers\etc\hosts";
+
+pub struct UninstallOptions {
+    pub keep_tools: bool,
+    pub keep_data: bool,
+    pub yes: bool,
+}
+
+pub fn uninstall(options: &UninstallOptions) -> Result<(), Box<dyn std::error::Error>> {
+    if !is_elevated() {
+        say!(
+            "{}",
+            "This command requires administrative privileges. Please run as administrator.".green()
+        );
+        return Err("Administrative privileges required".into());
+    }
+
+    say!("{}", "This will remove laracli from this machine:".yellow());
+    say!("   - Stop and delete the laracli service");
+    say!("   - Remove laracli from your PATH");
+    say!("   - Remove host entries added by laracli");
+    say!("   - Revert the file permissions granted by setup");
+    if !options.keep_tools {
+        say!("   - Delete downloaded tools (nginx, php, mysql, phpMyAdmin)");
+    }
+    if !options.keep_data {
+        say!("   - Delete MySQL databases and the laracli config");
+    }
+
+    if !options.yes && !confirm("Continue?")? {
+        say!("{}", "Uninstall cancelled.".blue());
+        return Ok(());
+    }
+
+    // Stop running dev processes so their files can be deleted
+    let _ = crate::commands::php::stop_php_cgi();
+    let _ = crate::commands::nginx::stop();
+    let _ = crate::commands::mysql::stop();
+
+    remove_services()?;
+    remove_exe_from_path()?;
+
+    match helpers::hosts::remove_laracli_host_entries() {
+        Ok(count) => say!("✅ Removed {} host entries", count),
+        Err(e) => say!("❌ Error removing host entries: {}", e),
+    }
+    revert_permissions();
+
+    if !options.keep_tools {
+        remove_tools(options.keep_data)?;
+    }
+
+    if !options.keep_data {
+        let config_path = helpers::config::get_config_path();
+        if config_path.exists() {
+            fs::remove_file(&config_path)?;
+            say!("✅ Removed config file: {}", config_path.display());
+        }
+    }
+
+    say!("{}", "✅ laracli has been uninstalled".green());
+    Ok(())
+}
+
+fn confirm(question: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    // A prompt would end up in the JSON result or go unseen with --quiet
+    if !laracli::helpers::output::is_text() {
+        return Err("Pass --yes to uninstall without a confirmation prompt".into());
+    }
+    print!("{} [y/N] ", question);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+fn remove_services() -> Result<(), Box<dyn std::error::Error>> {
+    let services = vec!["laracli", "laracli_config"];
+
+    for service_name in services {
+        let output = Command::new("sc").args(["query", service_name]).output()?;
+        if !output.status.success() {
+            say!("Service {} is not installed", service_name);
+            continue;
+        }
+
+        // Stopping fails when the service is already stopped, which is fine
+        let _ = Command::new("sc").args(["stop", service_name]).output()?;
+
+        let delete_output = Command::new("sc").args(["delete", service_name]).output()?;
+        if !delete_output.status.success() {
+            let stderr = String::from_utf8_lossy(&delete_output.stderr);
+            say!("Failed to delete {} service: {}", service_name, stderr);
+            return Err(format!("Failed to delete {} service: {}", service_name, stderr).into());
+        }
+
+        say!("✅ Removed {} service", service_name);
+    }
+
+    Ok(())
+}
+
+pub fn remove_exe_from_path() -> Result<(), Box<dyn std::error::Error>> {
+    let exe_dir = helpers::path::get_current_exe_dir()?;
+
+    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+    let env = hkcu.open_subkey_with_flags("Environment", KEY_READ | KEY_WRITE)?;
+    let current_path: String = env.get_value("Path").unwrap_or_default();
+
+    let remaining: Vec<&str> = current_path
+        .split(';')
+        .filter(|entry| !entry.is_empty() && !entry.eq_ignore_ascii_case(&exe_dir))
+        .collect();
+    let updated_path = remaining.join(";");
+
+    if updated_path != current_path {
+        env.set_value("Path", &updated_path)?;
+        notify_environment_change();
+        say!("✅ Removed {} from PATH", exe_dir);
+    } else {
+        say!("{}", "ℹ️ PATH does not contain the laracli directory.".blue());
+    }
+
+    Ok(())
+}
+
+// Take back the grants of `laracli setup`: LocalService on the hosts file, and the Users
+// group on the tools, which may be kept
+fn revert_permissions() {
+    let mut grants = vec![(HOSTS_PATH.to_string(), "*S-1-5-19")];
+    if let Ok(tools_dir) = helpers::path::get_tools_path()
+        && tools_dir.exists()
+    {
+        grants.push((tools_dir.to_string_lossy().into_owned(), "*S-1-5-32-545"));
+    }
+
+    for (path, sid) in grants {
+        match Command::new("icacls").args([path.as_str(), "/remove:g", sid, "/T"]).output() {
+            Ok(output) if output.status.success() => say!("✅ Reverted permissions on {}", path),
+            Ok(output) => say!(
+                "❌ Error reverting permissions on {}: {}",
+                path,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            Err(e) => say!("❌ Error reverting permissions on {}: {}", path, e),
+        }
+    }
+}
+
+fn remove_tools(keep_data: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let tools_dir = helpers::path::get_tools_path()?;
+    if !tools_dir.exists() {
+        return Ok(());
+    }
+
+    if !keep_data {
+        fs::remove_dir_all(&tools_dir)?;
+        say!("✅ Removed tools directory: {}", tools_dir.display());
+        return Ok(());
+    }
+
+    // Keep the MySQL data directory, delete everything else
+    let mysql_path = helpers::path::get_mysql_path()?;
+    let mysql_dir = Path::new(&mysql_path);
+    for entry in fs::read_dir(&tools_dir)? {
+        let path = entry?.path();
+        if path == mysql_dir {
+            for mysql_entry in fs::read_dir(&path)? {
+                let mysql_entry = mysql_entry?.path();
+                if mysql_entry.file_name().and_then(|n| n.to_str()) == Some("data") {
+                    continue;
+                }
+                remove_path(&mysql_entry)?;
+            }
+        } else {
+            remove_path(&path)?;
+        }
+    }
+
+    say!(
+        "✅ Removed tools, kept MySQL data in {}",
+        mysql_dir.join("data").display()
+    );
+    Ok(())
+}
+
+fn remove_path(path: &Path) -> io::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
@@ -1,29 +1,37 @@
 use windows_service::service::{ServiceAccess, ServiceErrorControl, ServiceInfo, ServiceStartType, ServiceType};
 use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
 use std::ffi::OsString;
-use std::fs::{self, OpenOptions};
-use std::io::{BufRead, BufReader, Write};
 use std::path::{PathBuf};
 use std::time::Duration;
 use std::env;
+use laracli::error::LaracliError;
+use laracli::helpers::output;
+use laracli::helpers::plan::{self, Step};
 use laracli::utils::elevate;
-use crate::helpers;
+use laracli::helpers;
+use laracli::helpers::ipc;
+use laracli::helpers::watch_rules::{WatchOptions, WatchRules};
+use laracli::say;
 
 
 
 
 /// Main watch command
-pub fn watch_directory(watch_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+pub fn watch_directory(
+    watch_path: &str,
+    options: WatchOptions,
+    update_env: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
     let watch_dir = PathBuf::from(watch_path);
-    println!("Setting up watch for directory: {:?}", watch_dir);
+    say!("Setting up watch for directory: {:?}", watch_dir);
 
     // Check if directory exists
     if !watch_dir.exists() {
-        return Err(format!("Directory does not exist: {:?}", watch_dir).into());
+        return Err(LaracliError::NotFound(format!("Directory does not exist: {:?}", watch_dir)).into());
     }
 
-    if !elevate::is_elevated() {
-        println!("🔒 Elevation required to modify hosts file. Requesting UAC permission...");
+    if !plan::is_dry_run() && !elevate::is_elevated() {
+        say!("🔒 Elevation required to modify hosts file. Requesting UAC permission...");
         elevate::run_as_admin()?;
         return Ok(()); // new process will be elevated
     }
@@ -32,74 +40,80 @@ pub fn watch_directory(watch_path: &str) -> Result<(), Box<dyn std::error::Error
 
     // Step 1: Save to config.json with normalized path
     
-    match helpers::config::add_to_watched_paths(&watch_dir.to_str().unwrap()) {
-        Ok(_) => {
-            println!("✅ Added directory to watch: {}", watch_dir.display());
-        }
-        Err(e) => {
-            println!("Directory already in config: {}", &e);
-        },
+    if helpers::config::add_to_watched_paths(watch_path)? {
+        say!("✅ Added directory to watch: {}", watch_dir.display());
+    } else {
+        say!("Directory already in config: {}", watch_dir.display());
     }
 
-    restart_service().expect("Failed to restart service");
+    helpers::config::set_watch_options(watch_path, options.clone())?;
+    if update_env {
+        let mut config = helpers::config::load_config()?;
+        config.update_env = true;
+        helpers::config::write_config(&config)?;
+    }
 
-    // Step 2: Add existing folders to hosts
-    println!("Scanning existing directories...");
-    for entry in fs::read_dir(&watch_dir)? {
-        let entry = entry?;
-        if entry.path().is_dir() {
-            if let Some(name) = entry.file_name().to_str() {
-                add_host_entry(name)?;
-            }
-        }
+    reload_service()?;
+
+    // Step 2: Link existing project folders
+    say!("Scanning existing directories...");
+    let config = helpers::config::load_config()?;
+    // Same root as the stored watched path, so the folders compare equal to the checked ones
+    let rules = WatchRules::load(
+        &PathBuf::from(helpers::config::normalize_path_string(watch_path)),
+        &options,
+    );
+    let (_, skipped) = helpers::sites::watched_projects_checked(&config);
+    let projects: Vec<PathBuf> = rules
+        .find_projects()
+        .into_iter()
+        .filter(|path| !helpers::sites::is_explicitly_linked(&config, path))
+        .filter(|path| !skipped.iter().any(|skipped| &skipped.path == path))
+        .collect();
+    for skipped in skipped.iter().filter(|skipped| skipped.path.starts_with(&rules.root)) {
+        say!("⚠️ Skipped {}: {}", skipped.path.display(), skipped.reason);
+    }
+    let report = helpers::sites::apply_changes(&config, &projects, &[]);
+    for name in &report.linked {
+        say!("✅ Linked {}.test", name);
+    }
+    for error in &report.errors {
+        say!("❌ {}", error);
     }
 
-    println!("✅ Directory watcher configuration updated!");
-    println!("The service will now monitor: {}", &watch_dir.display());
-    println!("New Laravel projects will automatically get .test domain entries.");
+    say!("✅ Directory watcher configuration updated!");
+    say!("The service will now monitor: {}", &watch_dir.display());
+    say!("New projects will automatically be served at <folder>.test.");
     
     Ok(())
 }
 
 
-/// Adds a host entry if not exists
-fn add_host_entry(project_name: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let entry = format!("127.0.0.1\t{}.test #added by laracli", project_name);
-    let hosts_path = r"C:\Windows\System32\drivers\etc\hosts";
-
-    // Check if entry already exists
-    let file = fs::File::open(hosts_path)?;
-    let reader = BufReader::new(file);
-
-    for line in reader.lines() {
-        let line = line?;
-        if line.contains(&format!("{}.test", project_name)) {
-            println!("Host entry for {}.test already exists.", project_name);
-            return Ok(());
-        }
-    }
-
-    // Add new entry
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(hosts_path)?;
-
-    writeln!(file, "{}", entry)?;
-    println!("✅ Added host entry for {}.test", project_name);
-    Ok(())
-}
-
 /// List all watched directories from config
 pub fn list_watched_directories() -> Result<(), Box<dyn std::error::Error>> {
-    let config = helpers::config::load_config();
-    
+    let config = helpers::config::load_config()?;
+    let watched: Vec<_> = config
+        .watched_paths
+        .iter()
+        .map(|path| serde_json::json!({ "path": path, "options": config.watch_options(path) }))
+        .collect();
+    output::set("watched_paths", watched);
+
     if config.watched_paths.is_empty() {
-        println!("No directories are currently being watched.");
+        say!("No directories are currently being watched.");
     } else {
-        println!("Currently watched directories:");
+        say!("Currently watched directories:");
         for path in &config.watched_paths {
-            println!("  📁 {}", path);
+            say!("  📁 {}", path);
+            if let Some(options) = config.watch_options.get(path) {
+                say!("     depth: {}", options.depth);
+                if !options.include.is_empty() {
+                    say!("     include: {}", options.include.join(", "));
+                }
+                if !options.exclude.is_empty() {
+                    say!("     exclude: {}", options.exclude.join(", "));
+                }
+            }
         }
     }
     
@@ -110,12 +124,10 @@ pub fn list_watched_directories() -> Result<(), Box<dyn std::error::Error>> {
 pub fn unwatch_directory(watch_path: &str) -> Result<(), Box<dyn std::error::Error>> {    
     match helpers::config::remove_from_watched_paths(watch_path) {
         Ok(_) => {
-            println!("Removed directory from watching: {}", watch_path);
-            restart_service()?;
+            say!("Removed directory from watching: {}", watch_path);
+            reload_service()?;
         }
-        Err(e) => {
-            println!("Directory not found in config: {}", &e);
-        },
+        Err(e) => return Err(e.into()),
     };
     Ok(())
 }
@@ -125,15 +137,27 @@ fn ensure_service_installed() -> Result<(), Box<dyn std::error::Error>> {
     let display_name = "Laracli Directory Watcher";
     let service_path = env::current_exe()?.with_file_name("laracli-service.exe");
 
-    let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CREATE_SERVICE | ServiceManagerAccess::CONNECT)?;
+    // A dry run only looks, which works without elevation
+    let access = if plan::is_dry_run() {
+        ServiceManagerAccess::CONNECT
+    } else {
+        ServiceManagerAccess::CREATE_SERVICE | ServiceManagerAccess::CONNECT
+    };
+    let manager = ServiceManager::local_computer(None::<&str>, access)?;
 
     // Check if service already exists
     match manager.open_service(service_name, ServiceAccess::QUERY_STATUS) {
         Ok(_) => {
-            println!("✅ Service `{}` already installed.", service_name);
+            say!("✅ Service `{}` already installed.", service_name);
         }
         Err(_) => {
-            println!("🔧 Installing `{}` service...", service_name);
+            if plan::dry_run(Step::Service {
+                name: format!("the {} service", service_name),
+                action: "Install and start".to_string(),
+            }) {
+                return Ok(());
+            }
+            say!("🔧 Installing `{}` service...", service_name);
             let service_info = ServiceInfo {
                 name: OsString::from(service_name),
                 display_name: OsString::from(display_name),
@@ -149,16 +173,37 @@ fn ensure_service_installed() -> Result<(), Box<dyn std::error::Error>> {
 
             let service = manager.create_service(&service_info, ServiceAccess::START)?;
             service.start::<OsString>(&[])?;
-            println!("✅ Service `{}` installed and started.", service_name);
+            say!("✅ Service `{}` installed and started.", service_name);
         }
     }
 
     Ok(())
 }
 
+/// Ask the daemon to reread config.json, restarting it when its endpoint is unavailable
+pub fn reload_service() -> Result<(), Box<dyn std::error::Error>> {
+    if plan::dry_run(Step::Service {
+        name: "the laracli service".to_string(),
+        action: "Reload".to_string(),
+    }) {
+        return Ok(());
+    }
+    match ipc::send(ipc::DAEMON_ENDPOINT, &ipc::Request::ReloadConfig) {
+        Ok(response) if response.ok => {
+            say!("✅ {}", response.message);
+            Ok(())
+        }
+        Ok(response) => Err(response.message.into()),
+        Err(e) => {
+            say!("⚠️ {}", e);
+            restart_service()
+        }
+    }
+}
+
 pub fn restart_service() -> Result<(), Box<dyn std::error::Error>> {
     if !elevate::is_elevated() {
-        println!("🔒 Elevation required to modify hosts file. Requesting UAC permission...");
+        say!("🔒 Elevation required to modify hosts file. Requesting UAC permission...");
         elevate::run_as_admin()?;
         return Ok(()); 
     }
@@ -167,12 +212,12 @@ pub fn restart_service() -> Result<(), Box<dyn std::error::Error>> {
     let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
     
     if let Ok(service) = manager.open_service(service_name, ServiceAccess::START | ServiceAccess::STOP | ServiceAccess::QUERY_STATUS) {
-        println!("🔄 Restarting service to apply configuration changes...");
+        say!("🔄 Restarting service to apply configuration changes...");
         
         // Stop the service
         if let Err(e) = service.stop() {
             // Service might not be running, that's ok
-            println!("Note: Error stopping service (might not be running): {}", e);
+            say!("Note: Error stopping service (might not be running): {}", e);
         }
         
         // Wait a moment for service to stop
@@ -180,9 +225,9 @@ pub fn restart_service() -> Result<(), Box<dyn std::error::Error>> {
         
         // Start the service
         service.start::<OsString>(&[])?;
-        println!("✅ Service restarted successfully.");
+        say!("✅ Service restarted successfully.");
     } else {
-        println!("⚠️  Could not restart service. You may need to restart it manually.");
+        say!("⚠️  Could not restart service. You may need to restart it manually.");
     }
     
     Ok(())
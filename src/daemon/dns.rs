@@ -0,0 +1,93 @@
+use std::net::{Ipv4Addr, UdpSocket};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+
+use crate::daemon::{Task, error, log};
+use crate::helpers::config::Config;
+use crate::helpers::dns::{self, DnsConfig};
+use crate::helpers::network::NetworkConfig;
+
+/// Runs the built-in resolver for the site TLD while `dns.enabled` is set
+#[derive(Default)]
+pub struct DnsResolver {
+    config: DnsConfig,
+    network: NetworkConfig,
+    server: Option<DnsServer>,
+    queries: Arc<AtomicU64>,
+}
+
+struct DnsServer {
+    running: Arc<AtomicBool>,
+    handle: thread::JoinHandle<()>,
+}
+
+impl DnsResolver {
+    fn apply(&mut self, config: &DnsConfig, network: &NetworkConfig) -> Result<(), Box<dyn std::error::Error>> {
+        if self.server.is_some() && *config == self.config && *network == self.network {
+            return Ok(());
+        }
+        self.stop_server();
+        self.config = config.clone();
+        self.network = network.clone();
+        if !config.enabled {
+            return Ok(());
+        }
+
+        let socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, config.port))
+            .map_err(|e| format!("Failed to bind DNS resolver to 127.0.0.1:{}: {}", config.port, e))?;
+        log(&format!("DNS resolver answering *.{} on 127.0.0.1:{}", dns::SITE_TLD, config.port));
+
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = running.clone();
+        let queries = self.queries.clone();
+        let addresses = network.host_addresses();
+        let handle = thread::spawn(move || {
+            if let Err(e) = dns::serve(socket, dns::SITE_TLD, &addresses, thread_running, queries) {
+                error(&format!("DNS resolver stopped: {}", e));
+            }
+        });
+        self.server = Some(DnsServer { running, handle });
+        Ok(())
+    }
+
+    fn stop_server(&mut self) {
+        if let Some(server) = self.server.take() {
+            server.running.store(false, Ordering::SeqCst);
+            let _ = server.handle.join();
+            log("DNS resolver stopped");
+        }
+    }
+}
+
+impl Task for DnsResolver {
+    fn name(&self) -> &'static str {
+        "dns"
+    }
+
+    fn start(&mut self, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+        self.apply(&config.dns, &config.network)
+    }
+
+    fn reload(&mut self, config: &Config) -> Result<String, Box<dyn std::error::Error>> {
+        self.apply(&config.dns, &config.network)?;
+        Ok(if self.server.is_some() {
+            format!("Answering *.{} on port {}", dns::SITE_TLD, self.config.port)
+        } else {
+            "Disabled".to_string()
+        })
+    }
+
+    fn status(&self) -> serde_json::Value {
+        serde_json::json!({
+            "enabled": self.server.is_some(),
+            "port": self.config.port,
+            "tld": dns::SITE_TLD,
+            "queries": self.queries.load(Ordering::Relaxed),
+        })
+    }
+
+    fn stop(&mut self) {
+        self.stop_server();
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::HashSet;
+
+use crate::daemon::{Task, error, log, read_config};
+use crate::helpers::config::Config;
+use crate::helpers::hosts;
+use crate::helpers::ipc::{Request, Response};
+use crate::helpers::nginx;
+
+/// Keeps host entries and nginx in line with `linked_paths` and `proxies`, and handles
+/// `add-site` / `remove-site` requests
+#[derive(Default)]
+pub struct LinkedPaths {
+    // Last applied config
+    state: Option<Config>,
+}
+
+impl Task for LinkedPaths {
+    fn name(&self) -> &'static str {
+        "linked"
+    }
+
+    fn start(&mut self, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+        self.state = Some(config.clone());
+        Ok(())
+    }
+
+    fn reload(&mut self, config: &Config) -> Result<String, Box<dyn std::error::Error>> {
+        let (added, removed) = sync_linked_paths(&mut self.state, config.clone());
+        Ok(format!("{} added, {} removed", added.len(), removed.len()))
+    }
+
+    fn handle(&mut self, request: &Request) -> Option<Response> {
+        // The name ends up in the hosts file, which any caller can otherwise write to
+        let names = match request {
+            Request::AddSite { name, .. } => vec![name.clone()],
+            Request::RemoveSite { name, subdomains, .. } => hosts::site_host_names(name, subdomains),
+            _ => Vec::new(),
+        };
+        for name in &names {
+            if let Err(e) = hosts::validate_host_name(name) {
+                error(&format!("linked_paths: rejected \"{}\": {}", name, e));
+                return Some(Response::error(e.to_string()));
+            }
+        }
+
+        match request {
+            Request::AddSite { name, .. } => {
+                if let Err(e) = hosts::add_host_entry(name) {
+                    error(&format!("linked_paths: failed to add {}.test: {}", name, e));
+                    return Some(Response::error(format!(
+                        "Failed to add host entry for {}.test: {}",
+                        name, e
+                    )));
+                }
+                // The CLI already saved the path, remember it so the config reload does not apply it twice
+                self.state = Some(read_config());
+                Some(match nginx::reload_nginx() {
+                    Ok(_) => Response::ok(format!("{}.test added", name)),
+                    Err(e) => Response::error(format!("Host added but Nginx reload failed: {}", e)),
+                })
+            }
+            Request::RemoveSite { name, .. } => {
+                // Subdomains registered for the site go with it
+                if let Err(e) = hosts::update_host_entries(&[], &names) {
+                    error(&format!("linked_paths: failed to remove {}.test: {}", name, e));
+                    return Some(Response::error(format!(
+                        "Failed to remove host entry for {}.test: {}",
+                        name, e
+                    )));
+                }
+                self.state = Some(read_config());
+                Some(match nginx::reload_nginx() {
+                    Ok(_) => Response::ok(format!("{}.test removed", name)),
+                    Err(e) => Response::error(format!("Host removed but Nginx reload failed: {}", e)),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn status(&self) -> serde_json::Value {
+        let linked_paths: Vec<String> = self
+            .state
+            .as_ref()
+            .map(|config| config.linked_paths.iter().cloned().collect())
+            .unwrap_or_default();
+        serde_json::json!({ "linked_paths": linked_paths })
+    }
+
+    fn stop(&mut self) {
+        self.state = None;
+    }
+}
+
+// Apply the difference between the last applied config and `current_config`,
+// returns the added and removed linked paths
+fn sync_linked_paths(
+    previous_config: &mut Option<Config>,
+    current_config: Config,
+) -> (Vec<String>, Vec<String>) {
+    let empty = HashSet::new();
+    let prev_set = previous_config
+        .as_ref()
+        .map(|c| &c.linked_paths)
+        .unwrap_or(&empty);
+
+    let added: Vec<_> = current_config.linked_paths.difference(prev_set).cloned().collect();
+    let removed: Vec<_> = prev_set.difference(&current_config.linked_paths).cloned().collect();
+
+    for path in &added {
+        log(&format!("linked_paths: added \"{}\"", path));
+    }
+    for path in &removed {
+        log(&format!("linked_paths: removed \"{}\"", path));
+    }
+
+    // Host names of sites and their subdomains, so subdomain changes of a kept path apply too
+    let previous_hosts = previous_config.as_ref().map(all_host_names).unwrap_or_default();
+    let current_hosts = all_host_names(&current_config);
+    let mut add_hosts: Vec<String> = current_hosts.difference(&previous_hosts).cloned().collect();
+    let mut remove_hosts: Vec<String> = previous_hosts.difference(&current_hosts).cloned().collect();
+    add_hosts.sort();
+    remove_hosts.sort();
+
+    if !add_hosts.is_empty() || !remove_hosts.is_empty() {
+        // All host changes in one rewrite of the hosts file, then a single reload
+        match hosts::update_host_entries(&add_hosts, &remove_hosts) {
+            Ok(_) => log(&format!(
+                "linked_paths: added {} and removed {} hosts",
+                add_hosts.len(),
+                remove_hosts.len()
+            )),
+            Err(e) => error(&format!("linked_paths: failed to update hosts: {}", e)),
+        }
+        match nginx::reload_nginx() {
+            Ok(_) => log("linked_paths: reloaded Nginx"),
+            Err(e) => error(&format!("linked_paths: failed to reload Nginx: {}", e)),
+        }
+    }
+
+    *previous_config = Some(current_config);
+    (added, removed)
+}
+
+fn all_host_names(config: &Config) -> HashSet<String> {
+    config
+        .linked_paths
+        .iter()
+        .flat_map(|path| config.host_names(path))
+        .chain(config.proxies.keys().cloned())
+        .collect()
+}
@@ -0,0 +1,268 @@
+//! The laracli daemon, run by the `laracli` Windows service or in the
+//! foreground with `laracli-service --foreground`.
+//!
+//! The daemon is a set of [`Task`]s sharing one config loader and one
+//! control endpoint: requests sent with [`crate::helpers::ipc::send`] are
+//! dispatched to the tasks.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Sender, channel};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::helpers::config::{self, Config};
+use crate::helpers::ipc::{self, Request, Response};
+use crate::helpers::journal;
+use crate::helpers::logger;
+use crate::helpers::logs;
+
+pub mod dns;
+pub mod linked;
+pub mod reconciler;
+pub mod supervisor;
+pub mod watcher;
+
+const TICK_INTERVAL: Duration = Duration::from_secs(10);
+const CONFIG_DEBOUNCE: Duration = Duration::from_millis(200);
+// Longest pause a request may ask for
+const MAX_PAUSE: Duration = Duration::from_secs(300);
+
+pub use crate::helpers::logger::{debug, error, warn};
+
+/// Also print log lines to the console, used when running in the foreground
+pub fn set_foreground(foreground: bool) {
+    logger::set_console(foreground);
+}
+
+pub fn log(msg: &str) {
+    logger::info(msg);
+}
+
+/// A unit of work hosted by the daemon
+pub trait Task: Send {
+    fn name(&self) -> &'static str;
+    /// Called once when the daemon starts
+    fn start(&mut self, config: &Config) -> Result<(), Box<dyn std::error::Error>>;
+    /// Apply a freshly read config, returns a short summary of what changed
+    fn reload(&mut self, config: &Config) -> Result<String, Box<dyn std::error::Error>>;
+    /// Handle a control request, `None` when the task does not handle it
+    fn handle(&mut self, _request: &Request) -> Option<Response> {
+        None
+    }
+    /// Called every few seconds while the daemon runs
+    fn tick(&mut self) {}
+    fn status(&self) -> serde_json::Value;
+    /// Called once when the daemon stops
+    fn stop(&mut self);
+}
+
+pub fn default_tasks() -> Vec<Box<dyn Task>> {
+    vec![
+        Box::new(watcher::DirectoryWatchers::default()),
+        Box::new(linked::LinkedPaths::default()),
+        Box::new(reconciler::Reconciler::default()),
+        Box::new(supervisor::Supervisor::default()),
+        Box::new(dns::DnsResolver::default()),
+    ]
+}
+
+pub fn read_config() -> Config {
+    match config::read_config() {
+        Ok(config) => config,
+        Err(e) => {
+            error(&e.to_string());
+            Config::default()
+        }
+    }
+}
+
+/// Run the default tasks until `running` is cleared
+pub fn run(running: Arc<AtomicBool>) -> Result<(), Box<dyn std::error::Error>> {
+    run_tasks(default_tasks(), running)
+}
+
+pub fn run_tasks(
+    tasks: Vec<Box<dyn Task>>,
+    running: Arc<AtomicBool>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config = read_config();
+    logger::init(logs::SERVICE_LOG, &config.logging);
+    log(&format!("Daemon started with PID: {}", std::process::id()));
+    journal::record(journal::EventKind::ServiceStarted, "laracli", &format!("PID {}", std::process::id()));
+    log(&format!("Using config: {:?}", config::get_config_path()));
+    let tasks = Arc::new(Mutex::new(tasks));
+
+    for task in tasks.lock().unwrap().iter_mut() {
+        log(&format!("Starting task: {}", task.name()));
+        if let Err(e) = task.start(&config) {
+            error(&format!("Task {} failed to start: {}", task.name(), e));
+        }
+    }
+
+    // Set by a `pause` request, config reloads and ticks wait until then
+    let paused_until: Arc<Mutex<Option<Instant>>> = Arc::new(Mutex::new(None));
+
+    // Control endpoint used by the CLI instead of restarting the service
+    let ipc_running = running.clone();
+    let ipc_tasks = tasks.clone();
+    let ipc_paused_until = paused_until.clone();
+    let ipc_thread = thread::spawn(move || {
+        if let Err(e) = ipc::serve(ipc::DAEMON_ENDPOINT, ipc_running, move |request| {
+            handle_request(&ipc_tasks, &ipc_paused_until, request)
+        }) {
+            error(&format!("Control endpoint failed: {}", e));
+        }
+    });
+
+    // Reload every task when config.json is edited, by the CLI or by hand
+    let (config_tx, config_rx) = channel();
+    let _config_watcher = match watch_config_file(config_tx) {
+        Ok(watcher) => Some(watcher),
+        Err(e) => {
+            error(&format!("Failed to watch config file: {}", e));
+            None
+        }
+    };
+
+    let mut last_tick = Instant::now();
+    let mut config_changed = false;
+    while running.load(Ordering::SeqCst) {
+        if config_rx.recv_timeout(Duration::from_secs(1)).is_ok() {
+            // Editors and serde write the file in several steps, wait for the burst to end
+            thread::sleep(CONFIG_DEBOUNCE);
+            while config_rx.try_recv().is_ok() {}
+            config_changed = true;
+        }
+
+        // A link or unlink is writing several files, and may still roll them back
+        let paused = {
+            let mut paused_until = paused_until.lock().unwrap();
+            if paused_until.is_some_and(|until| Instant::now() >= until) {
+                log("Pause timed out, resuming");
+                *paused_until = None;
+            }
+            paused_until.is_some()
+        };
+        if paused {
+            continue;
+        }
+
+        if config_changed {
+            config_changed = false;
+            log("Config file changed, reloading tasks");
+            reload_tasks(&mut tasks.lock().unwrap());
+        }
+
+        if last_tick.elapsed() >= TICK_INTERVAL {
+            for task in tasks.lock().unwrap().iter_mut() {
+                task.tick();
+            }
+            last_tick = Instant::now();
+        }
+    }
+
+    log("Daemon stopping, waiting for tasks to finish...");
+    for task in tasks.lock().unwrap().iter_mut() {
+        task.stop();
+    }
+    let _ = ipc_thread.join();
+
+    log("Daemon stopped.");
+    journal::record(journal::EventKind::ServiceStopped, "laracli", "");
+    Ok(())
+}
+
+fn handle_request(
+    tasks: &Mutex<Vec<Box<dyn Task>>>,
+    paused_until: &Mutex<Option<Instant>>,
+    request: Request,
+) -> Response {
+    debug(&format!("Control request: {:?}", request));
+    let mut tasks = tasks.lock().unwrap();
+
+    match request {
+        Request::ReloadConfig => reload_tasks(&mut tasks),
+        Request::Pause { seconds } => {
+            let pause = Duration::from_secs(seconds).min(MAX_PAUSE);
+            *paused_until.lock().unwrap() = Some(Instant::now() + pause);
+            Response::ok(format!("Paused for {} seconds", pause.as_secs()))
+        }
+        // The main loop applies the config changes made during the pause
+        Request::Resume => {
+            *paused_until.lock().unwrap() = None;
+            Response::ok("Resumed")
+        }
+        Request::Status => {
+            let mut statuses = serde_json::Map::new();
+            for task in tasks.iter() {
+                statuses.insert(task.name().to_string(), task.status());
+            }
+            Response::ok(format!("laracli daemon running with {} tasks", tasks.len())).with_data(
+                serde_json::json!({
+                    "pid": std::process::id(),
+                    "config": config::get_config_path(),
+                    "tasks": statuses,
+                }),
+            )
+        }
+        request => tasks
+            .iter_mut()
+            .find_map(|task| task.handle(&request))
+            .unwrap_or_else(|| Response::error(format!("No task handles {:?}", request))),
+    }
+}
+
+// Apply a fresh read of config.json to every task
+fn reload_tasks(tasks: &mut [Box<dyn Task>]) -> Response {
+    let config = read_config();
+    logger::configure(&config.logging);
+    let mut summary = serde_json::Map::new();
+    let mut errors = Vec::new();
+    for task in tasks.iter_mut() {
+        match task.reload(&config) {
+            Ok(message) => {
+                log(&format!("Task {} reloaded: {}", task.name(), message));
+                summary.insert(task.name().to_string(), message.into());
+            }
+            Err(e) => {
+                error(&format!("Task {} failed to reload: {}", task.name(), e));
+                errors.push(format!("{}: {}", task.name(), e));
+            }
+        }
+    }
+
+    let response = if errors.is_empty() {
+        Response::ok("Config reloaded")
+    } else {
+        Response::error(errors.join("; "))
+    };
+    response.with_data(summary.into())
+}
+
+// Watch the directory holding config.json, the file itself is replaced on save
+fn watch_config_file(tx: Sender<()>) -> Result<RecommendedWatcher, Box<dyn std::error::Error>> {
+    let config_path = config::get_config_path();
+    let config_dir = config_path
+        .parent()
+        .ok_or("Config file has no parent directory")?
+        .to_path_buf();
+    let file_name = config_path.file_name().map(|name| name.to_os_string());
+
+    let mut watcher = notify::recommended_watcher(move |res: Result<notify::Event, notify::Error>| {
+        if let Ok(event) = res {
+            let is_config = event
+                .paths
+                .iter()
+                .any(|path| path.file_name().map(|name| name.to_os_string()) == file_name);
+            if is_config && matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                let _ = tx.send(());
+            }
+        }
+    })?;
+    watcher.watch(&config_dir, RecursiveMode::NonRecursive)?;
+    log(&format!("Watching config file: {:?}", config_path));
+    Ok(watcher)
+}
@@ -0,0 +1,145 @@
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use crate::daemon::{Task, error, log, read_config};
+use crate::helpers::config::Config;
+use crate::helpers::ipc::{Request, Response};
+use crate::helpers::reconcile;
+use crate::helpers::sites::{self, SkippedFolder};
+
+// How often the daemon converges nginx and hosts on its own
+const RECONCILE_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Periodically compares config.json with nginx, hosts and the linked
+/// directories and fixes whatever drifted, also on `sync` requests.
+/// Only a `sync` request unlinks directories that no longer exist
+#[derive(Default)]
+pub struct Reconciler {
+    last_run: Option<Instant>,
+    last_changes: usize,
+    // Missing linked directories already logged
+    reported_missing: HashSet<String>,
+    // Watched folders skipped for a taken name, already logged
+    reported_skipped: HashSet<SkippedFolder>,
+}
+
+impl Reconciler {
+    fn sync(&mut self, dry_run: bool, requested: bool) -> Response {
+        if !dry_run {
+            self.last_run = Some(Instant::now());
+        }
+
+        let planned = if requested {
+            reconcile::plan_current().map(|actions| (actions, Vec::new()))
+        } else {
+            reconcile::plan_current_keeping_missing()
+        };
+        let actions = match planned {
+            Ok((actions, missing)) => {
+                self.report_missing(missing);
+                self.report_skipped();
+                actions
+            }
+            Err(e) => {
+                error(&format!("reconciler: failed to read state: {}", e));
+                return Response::error(format!("Failed to read state: {}", e));
+            }
+        };
+
+        if dry_run {
+            return Response::ok(format!("{} changes planned", actions.len()))
+                .with_data(serde_json::json!({ "actions": actions }));
+        }
+
+        self.last_changes = actions.len();
+
+        let outcomes = reconcile::apply(&actions);
+        let mut failed = 0;
+        for outcome in &outcomes {
+            match &outcome.error {
+                Some(e) => {
+                    failed += 1;
+                    error(&format!("reconciler: failed to {}: {}", outcome.action, e));
+                }
+                None => log(&format!("reconciler: {}", outcome.action)),
+            }
+        }
+
+        let data = serde_json::json!({ "actions": outcomes });
+
+        if failed > 0 {
+            Response::error(format!("{} of {} changes failed", failed, outcomes.len())).with_data(data)
+        } else {
+            Response::ok(format!("{} changes applied", outcomes.len())).with_data(data)
+        }
+    }
+
+    // Log each skipped folder once, the plan already leaves them out
+    fn report_skipped(&mut self) {
+        let skipped: HashSet<SkippedFolder> =
+            sites::watched_projects_checked(&read_config()).1.into_iter().collect();
+        for folder in skipped.difference(&self.reported_skipped) {
+            log(&format!("reconciler: skipped {}, {}", folder.path.display(), folder.reason));
+        }
+        self.reported_skipped = skipped;
+    }
+
+    // Log each missing directory once, until it is back or unlinked
+    fn report_missing(&mut self, missing: Vec<String>) {
+        let missing: HashSet<String> = missing.into_iter().collect();
+        for path in missing.difference(&self.reported_missing) {
+            log(&format!(
+                "reconciler: linked directory {} no longer exists, run `laracli sync` to unlink it",
+                path
+            ));
+        }
+        self.reported_missing = missing;
+    }
+}
+
+impl Task for Reconciler {
+    fn name(&self) -> &'static str {
+        "reconciler"
+    }
+
+    fn start(&mut self, _config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+        // Catch up on whatever changed while the service was down
+        self.sync(false, false);
+        Ok(())
+    }
+
+    fn reload(&mut self, _config: &Config) -> Result<String, Box<dyn std::error::Error>> {
+        // Watched paths may have been added or removed, link or unlink their projects
+        let response = self.sync(false, false);
+        if response.ok {
+            Ok(response.message)
+        } else {
+            Err(response.message.into())
+        }
+    }
+
+    fn handle(&mut self, request: &Request) -> Option<Response> {
+        match request {
+            Request::Sync { dry_run } => Some(self.sync(*dry_run, true)),
+            _ => None,
+        }
+    }
+
+    fn tick(&mut self) {
+        if self
+            .last_run
+            .is_none_or(|last_run| last_run.elapsed() >= RECONCILE_INTERVAL)
+        {
+            self.sync(false, false);
+        }
+    }
+
+    fn status(&self) -> serde_json::Value {
+        serde_json::json!({
+            "last_run_secs_ago": self.last_run.map(|last_run| last_run.elapsed().as_secs()),
+            "last_changes": self.last_changes,
+        })
+    }
+
+    fn stop(&mut self) {}
+}
@@ -0,0 +1,80 @@
+use std::collections::HashSet;
+
+use crate::daemon::{Task, log};
+use crate::helpers::config::Config;
+use crate::helpers::process::DevProcess;
+
+/// Watches php-cgi, nginx and mysqld for the processes listed in the `supervise`
+/// config entry and logs when they stop. The service runs as LocalSystem, so
+/// `laracli start-dev --supervise` restarts them in the user's session instead
+#[derive(Default)]
+pub struct Supervisor {
+    processes: HashSet<DevProcess>,
+    // Supervised processes already logged as stopped
+    stopped: HashSet<DevProcess>,
+}
+
+impl Supervisor {
+    fn apply(&mut self, config: &Config) {
+        self.processes = config
+            .supervise
+            .iter()
+            .filter_map(|name| {
+                let process = DevProcess::from_name(name);
+                if process.is_none() {
+                    log(&format!("supervisor: unknown process \"{}\"", name));
+                }
+                process
+            })
+            .collect();
+        self.stopped.retain(|process| self.processes.contains(process));
+    }
+}
+
+impl Task for Supervisor {
+    fn name(&self) -> &'static str {
+        "supervisor"
+    }
+
+    fn start(&mut self, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+        self.apply(config);
+        Ok(())
+    }
+
+    fn reload(&mut self, config: &Config) -> Result<String, Box<dyn std::error::Error>> {
+        self.apply(config);
+        Ok(format!("{} processes supervised", self.processes.len()))
+    }
+
+    fn tick(&mut self) {
+        for process in DevProcess::ALL {
+            if !self.processes.contains(&process) {
+                continue;
+            }
+            if process.is_running() {
+                if self.stopped.remove(&process) {
+                    log(&format!("supervisor: {} is running again", process.name()));
+                }
+            } else if self.stopped.insert(process) {
+                log(&format!(
+                    "supervisor: {} is not running, `laracli start-dev --supervise` restarts it",
+                    process.name()
+                ));
+            }
+        }
+    }
+
+    fn status(&self) -> serde_json::Value {
+        let processes: serde_json::Map<String, serde_json::Value> = DevProcess::ALL
+            .into_iter()
+            .filter(|process| self.processes.contains(process))
+            .map(|process| (process.name().to_string(), process.is_running().into()))
+            .collect();
+        serde_json::json!({ "processes": processes })
+    }
+
+    fn stop(&mut self) {
+        // Supervised processes keep running, `laracli stop-dev` stops them
+        self.processes.clear();
+    }
+}
@@ -0,0 +1,430 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::channel;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use notify::event::{ModifyKind, RenameMode};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::daemon::{Task, debug, error, log, read_config, warn};
+use crate::helpers::config::Config;
+use crate::helpers::watch_rules::{WatchOptions, WatchRules};
+use crate::helpers::sites;
+
+/// Watches the directories in `watched_paths` and links every project folder
+/// created in them, unlinking it again when it is removed or renamed.
+/// Directories are started and stopped individually when `watched_paths`
+/// or their `watch_options` change.
+#[derive(Default)]
+pub struct DirectoryWatchers {
+    watchers: HashMap<PathBuf, DirectoryWatcher>,
+}
+
+// One watcher thread, the options it was started with and the flag that stops it
+struct DirectoryWatcher {
+    options: WatchOptions,
+    running: Arc<AtomicBool>,
+    handle: thread::JoinHandle<()>,
+}
+
+impl DirectoryWatcher {
+    fn spawn(path: PathBuf, options: WatchOptions) -> DirectoryWatcher {
+        let running = Arc::new(AtomicBool::new(true));
+        let running_clone = running.clone();
+        let thread_options = options.clone();
+        let handle = thread::spawn(move || {
+            if let Err(e) = watch_directory(&path, &thread_options, running_clone) {
+                error(&format!("Error watching {:?}: {}", path, e));
+            }
+        });
+        DirectoryWatcher {
+            options,
+            running,
+            handle,
+        }
+    }
+
+    fn stop(self) {
+        self.running.store(false, Ordering::SeqCst);
+        let _ = self.handle.join();
+    }
+}
+
+impl DirectoryWatchers {
+    // Start watchers for new paths and stop the ones no longer configured,
+    // returns how many were started and stopped
+    fn apply(&mut self, config: &Config) -> (usize, usize) {
+        let mut wanted = HashMap::new();
+        for dir in &config.watched_paths {
+            let path = normalize_path(dir);
+            if path.exists() {
+                wanted.insert(path, config.watch_options(dir));
+            } else {
+                warn(&format!("Configured path does not exist: {}", dir));
+            }
+        }
+
+        // Watchers whose path is gone or whose options changed are stopped
+        let removed: Vec<PathBuf> = self
+            .watchers
+            .iter()
+            .filter(|(path, watcher)| wanted.get(*path) != Some(&watcher.options))
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in &removed {
+            if let Some(watcher) = self.watchers.remove(path) {
+                log(&format!("Stopping watcher for {:?}", path));
+                watcher.stop();
+            }
+        }
+
+        let mut started = 0;
+        for (path, options) in wanted {
+            if !self.watchers.contains_key(&path) {
+                log(&format!("Starting watcher for {:?} (depth {})", path, options.depth));
+                self.watchers
+                    .insert(path.clone(), DirectoryWatcher::spawn(path, options));
+                started += 1;
+            }
+        }
+
+        (started, removed.len())
+    }
+
+    fn stop_all(&mut self) {
+        for (_, watcher) in self.watchers.drain() {
+            watcher.stop();
+        }
+    }
+}
+
+impl Task for DirectoryWatchers {
+    fn name(&self) -> &'static str {
+        "watcher"
+    }
+
+    fn start(&mut self, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+        let (started, _) = self.apply(config);
+        log(&format!("Watching {} directories", started));
+        Ok(())
+    }
+
+    fn reload(&mut self, config: &Config) -> Result<String, Box<dyn std::error::Error>> {
+        let (started, stopped) = self.apply(config);
+        Ok(format!(
+            "Watching {} directories ({} started, {} stopped)",
+            self.watchers.len(),
+            started,
+            stopped
+        ))
+    }
+
+    fn status(&self) -> serde_json::Value {
+        let paths: Vec<&PathBuf> = self.watchers.keys().collect();
+        let implicit_sites: Vec<String> = sites::watched_projects(&read_config())
+            .into_iter()
+            .map(|(name, _)| format!("{}.test", name))
+            .collect();
+        serde_json::json!({ "watched_paths": paths, "implicit_sites": implicit_sites })
+    }
+
+    fn stop(&mut self) {
+        self.stop_all();
+    }
+}
+
+// Fix path normalization to remove Windows extended-length prefix
+fn normalize_path(path_str: &str) -> PathBuf {
+    let path = PathBuf::from(path_str);
+
+    // Remove Windows extended-length path prefix if present
+    if let Ok(canonical) = path.canonicalize() {
+        let canonical_str = canonical.to_string_lossy();
+        if let Some(stripped) = canonical_str.strip_prefix("\\\\?\\") {
+            PathBuf::from(stripped)
+        } else {
+            canonical
+        }
+    } else {
+        path
+    }
+}
+
+fn watch_directory(
+    watch_dir: &Path,
+    options: &WatchOptions,
+    running: Arc<AtomicBool>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (tx, rx) = channel();
+    let mut watcher: RecommendedWatcher = notify::recommended_watcher(tx)?;
+    let mut tree = WatchTree {
+        rules: WatchRules::load(watch_dir, options),
+        subscribed: HashSet::new(),
+        batch: ChangeBatch::default(),
+    };
+    tree.subscribe(&mut watcher, watch_dir);
+    log(&format!(
+        "Started watching: {:?} ({} directories)",
+        watch_dir,
+        tree.subscribed.len()
+    ));
+
+    while running.load(Ordering::SeqCst) {
+        match rx.recv_timeout(tree.batch.wait_time()) {
+            Ok(Ok(event)) => {
+                if event.paths.contains(&tree.rules.ignore_file()) {
+                    log(&format!("Reloading {:?}", tree.rules.ignore_file()));
+                    tree.rules = WatchRules::load(watch_dir, options);
+                    continue;
+                }
+                tree.handle_event(&mut watcher, event);
+            }
+
+            Ok(Err(e)) => warn(&format!("Watch error: {:?}", e)),
+            Err(_) => {} // Timeout is normal
+        }
+
+        if tree.batch.is_due() {
+            tree.flush(&mut watcher);
+        }
+    }
+
+    log(&format!("Stopped watching: {:?}", watch_dir));
+    Ok(())
+}
+
+// Wait this long without events before applying a batch
+const QUIET_PERIOD: Duration = Duration::from_millis(750);
+// but never hold changes longer than this during a constant stream of events
+const MAX_BATCH_DELAY: Duration = Duration::from_secs(5);
+const IDLE_WAIT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Change {
+    Added,
+    Removed,
+    // Files changed inside a folder that was not a project yet
+    Check,
+}
+
+/// Folder changes collected from a burst of events (git clone, composer
+/// create-project, mass renames), only the last change of each folder counts
+#[derive(Default)]
+struct ChangeBatch {
+    changes: HashMap<PathBuf, Change>,
+    first_change: Option<Instant>,
+    last_change: Option<Instant>,
+}
+
+impl ChangeBatch {
+    fn record(&mut self, path: &Path, change: Change) {
+        let now = Instant::now();
+        self.first_change.get_or_insert(now);
+        self.last_change = Some(now);
+        if change == Change::Check {
+            self.changes.entry(path.to_path_buf()).or_insert(change);
+        } else {
+            self.changes.insert(path.to_path_buf(), change);
+        }
+    }
+
+    fn wait_time(&self) -> Duration {
+        match (self.first_change, self.last_change) {
+            (Some(first), Some(last)) => QUIET_PERIOD
+                .saturating_sub(last.elapsed())
+                .min(MAX_BATCH_DELAY.saturating_sub(first.elapsed())),
+            _ => IDLE_WAIT,
+        }
+    }
+
+    fn is_due(&self) -> bool {
+        !self.changes.is_empty() && self.wait_time().is_zero()
+    }
+
+    fn take(&mut self) -> HashMap<PathBuf, Change> {
+        self.first_change = None;
+        self.last_change = None;
+        std::mem::take(&mut self.changes)
+    }
+}
+
+// Directories of one watched path that are subscribed to, each non-recursively:
+// the watched path, folders above the project depth, and candidate folders
+// that are not projects yet (to notice their files appearing)
+struct WatchTree {
+    rules: WatchRules,
+    subscribed: HashSet<PathBuf>,
+    batch: ChangeBatch,
+}
+
+impl WatchTree {
+    fn subscribe(&mut self, watcher: &mut RecommendedWatcher, dir: &Path) {
+        if self.subscribed.contains(dir) {
+            return;
+        }
+        if let Err(e) = watcher.watch(dir, RecursiveMode::NonRecursive) {
+            error(&format!("Failed to watch {:?}: {}", dir, e));
+            return;
+        }
+        self.subscribed.insert(dir.to_path_buf());
+
+        if !self.rules.should_descend(dir) {
+            return;
+        }
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() && !self.rules.is_excluded(&path) && !sites::is_project(&path) {
+                self.subscribe(watcher, &path);
+            }
+        }
+    }
+
+    // Drop the subscription of `dir` and everything below it
+    fn unsubscribe(&mut self, watcher: &mut RecommendedWatcher, dir: &Path) {
+        let below: Vec<PathBuf> = self
+            .subscribed
+            .iter()
+            .filter(|path| path.starts_with(dir))
+            .cloned()
+            .collect();
+        for path in below {
+            let _ = watcher.unwatch(&path);
+            self.subscribed.remove(&path);
+        }
+    }
+
+    fn added(&mut self, watcher: &mut RecommendedWatcher, path: &Path) {
+        if !path.is_dir() || self.rules.is_excluded(path) {
+            return;
+        }
+        if self.rules.is_candidate(path) {
+            self.batch.record(path, Change::Added);
+        } else if self.rules.level(path).is_some_and(|level| level <= self.rules.depth) {
+            self.subscribe(watcher, path);
+        }
+    }
+
+    fn removed(&mut self, watcher: &mut RecommendedWatcher, path: &Path) {
+        self.unsubscribe(watcher, path);
+        if self.rules.is_candidate(path) {
+            self.batch.record(path, Change::Removed);
+        }
+    }
+
+    fn handle_event(&mut self, watcher: &mut RecommendedWatcher, event: notify::Event) {
+        debug(&format!("Event {:?} {:?}", event.kind, event.paths));
+        match event.kind {
+            EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+                for path in &event.paths {
+                    self.added(watcher, path);
+                }
+            }
+            EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                for path in &event.paths {
+                    self.removed(watcher, path);
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+                if let [from, to] = event.paths.as_slice() {
+                    self.removed(watcher, from);
+                    self.added(watcher, to);
+                }
+            }
+            _ => {}
+        }
+
+        // Files appearing inside a pending folder can turn it into a project
+        for path in &event.paths {
+            if let Some(parent) = path.parent()
+                && self.subscribed.contains(parent)
+                && self.rules.is_candidate(parent)
+            {
+                self.batch.record(parent, Change::Check);
+            }
+        }
+    }
+
+    // Apply the collected changes: one hosts rewrite and one nginx reload per batch
+    fn flush(&mut self, watcher: &mut RecommendedWatcher) {
+        let config = read_config();
+        let mut added = Vec::new();
+        let mut removed = Vec::new();
+
+        for (path, change) in self.batch.take() {
+            if sites::is_explicitly_linked(&config, &path) {
+                continue;
+            }
+            match change {
+                Change::Removed if !path.exists() => removed.push(path),
+                _ if sites::is_project(&path) => {
+                    self.unsubscribe(watcher, &path);
+                    added.push(path);
+                }
+                // Not a project yet, watch it to notice its files appearing
+                Change::Added => self.subscribe(watcher, &path),
+                _ => {}
+            }
+        }
+
+        if added.is_empty() && removed.is_empty() {
+            return;
+        }
+
+        // With --depth above 1 two folders can share a name, only one of them is served
+        let (projects, skipped) = sites::watched_projects_checked(&config);
+        added.retain(|path| match skipped.iter().find(|skipped| &skipped.path == path) {
+            Some(skipped) => {
+                log(&format!("Skipped {}: {}", path.display(), skipped.reason));
+                false
+            }
+            None => true,
+        });
+        removed.retain(|path| {
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                return true;
+            };
+            if config.find_site(name).is_some() || config.proxies.keys().any(|proxy| proxy.eq_ignore_ascii_case(name)) {
+                return false;
+            }
+            // A skipped folder of the same name takes over the site
+            match projects.iter().find(|(project, other)| project.eq_ignore_ascii_case(name) && other != path) {
+                Some((_, other)) => {
+                    if !sites::is_explicitly_linked(&config, other) && !added.contains(other) {
+                        added.push(other.clone());
+                    }
+                    false
+                }
+                None => true,
+            }
+        });
+
+        if added.is_empty() && removed.is_empty() {
+            return;
+        }
+        added.sort();
+        removed.sort();
+
+        let report = sites::apply_changes(&config, &added, &removed);
+        for name in &report.linked {
+            log(&format!("Linked {}.test", name));
+        }
+        for name in &report.unlinked {
+            log(&format!("Unlinked {}.test", name));
+        }
+        for message in &report.errors {
+            error(&format!("Failed to apply change: {}", message));
+        }
+        if report.nginx_reloaded {
+            log(&format!(
+                "Applied {} changes, reloaded Nginx once",
+                report.linked.len() + report.unlinked.len()
+            ));
+        }
+    }
+}
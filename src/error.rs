@@ -0,0 +1,221 @@
+//! Errors shown to the user with a hint on how to fix them. Every kind has
+//! its own process exit code so scripts can tell a missing tool from a busy
+//! port, anything else exits with 1.
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum LaracliError {
+    /// A command line argument that cannot be used
+    InvalidArgument(String),
+    /// config.json could not be read or parsed
+    ConfigError { path: PathBuf, message: String },
+    /// A bundled tool is not installed in tools/
+    ToolMissing { tool: &'static str, path: PathBuf },
+    PortInUse { service: &'static str, address: SocketAddr },
+    PermissionDenied { action: String },
+    /// `nginx -t` rejected the configuration
+    NginxInvalid { output: String },
+    DownloadFailed { what: String, message: String },
+    /// A site, proxy or watched directory that is not configured
+    NotFound(String),
+    /// The laracli service does not answer on its control endpoint
+    ServiceUnavailable(String),
+    /// A dev process (php, nginx, mysql) that has to be running is not
+    NotRunning { service: &'static str },
+    /// A step of a change failed and the steps before it were undone
+    RolledBack {
+        step: String,
+        error: Box<LaracliError>,
+        undone: Vec<String>,
+        /// Steps whose undo failed too, with the reason
+        not_undone: Vec<String>,
+    },
+    /// Any other failure, carried as its message
+    Other(String),
+}
+
+impl LaracliError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            LaracliError::InvalidArgument(_) => 2,
+            LaracliError::ConfigError { .. } => 3,
+            LaracliError::ToolMissing { .. } => 4,
+            LaracliError::PortInUse { .. } => 5,
+            LaracliError::PermissionDenied { .. } => 6,
+            LaracliError::NginxInvalid { .. } => 7,
+            LaracliError::DownloadFailed { .. } => 8,
+            LaracliError::NotFound(_) => 9,
+            LaracliError::ServiceUnavailable(_) => 10,
+            LaracliError::NotRunning { .. } => 11,
+            LaracliError::RolledBack { error, .. } => error.exit_code(),
+            LaracliError::Other(_) => 1,
+        }
+    }
+
+    pub fn hint(&self) -> Option<String> {
+        match self {
+            LaracliError::InvalidArgument(_) => None,
+            LaracliError::ConfigError { path, .. } => Some(format!(
+                "Fix or remove {}, a default one is created by `laracli setup`",
+                path.display()
+            )),
+            LaracliError::ToolMissing { .. } => {
+                Some("Download the tools with `laracli setup`".to_string())
+            }
+            LaracliError::PortInUse { address, .. } => Some(format!(
+                "Stop the program listening on {}, `netstat -ano | findstr :{}` shows its PID",
+                address,
+                address.port()
+            )),
+            LaracliError::PermissionDenied { .. } => {
+                Some("Run the command again from an elevated terminal".to_string())
+            }
+            LaracliError::NginxInvalid { .. } => Some(
+                "Fix the file named above, site configs are rewritten by `laracli sync`".to_string(),
+            ),
+            LaracliError::DownloadFailed { .. } => {
+                Some("Check the internet connection and run the command again".to_string())
+            }
+            LaracliError::NotFound(_) => None,
+            LaracliError::ServiceUnavailable(_) => Some(
+                "Start it with `sc start laracli`, or install it with `laracli setup`".to_string(),
+            ),
+            LaracliError::NotRunning { .. } => Some("Start it with `laracli start-dev`".to_string()),
+            LaracliError::RolledBack { not_undone, .. } if !not_undone.is_empty() => Some(
+                "Run `laracli sync` to fix what could not be undone".to_string(),
+            ),
+            LaracliError::RolledBack { error, .. } => error.hint(),
+            LaracliError::Other(_) => None,
+        }
+    }
+
+    /// An io error of `action`, reported as `PermissionDenied` when access was refused
+    pub fn from_io(action: &str, error: io::Error) -> LaracliError {
+        if error.kind() == io::ErrorKind::PermissionDenied {
+            LaracliError::PermissionDenied {
+                action: action.to_string(),
+            }
+        } else {
+            LaracliError::Other(format!("Failed to {}: {}", action, error))
+        }
+    }
+}
+
+impl fmt::Display for LaracliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LaracliError::InvalidArgument(message) => write!(f, "{}", message),
+            LaracliError::ConfigError { path, message } => {
+                write!(f, "Invalid config file {}: {}", path.display(), message)
+            }
+            LaracliError::ToolMissing { tool, path } => {
+                write!(f, "{} is not installed, {} was not found", tool, path.display())
+            }
+            LaracliError::PortInUse { service, address } => {
+                write!(f, "{} cannot start, {} is already in use", service, address)
+            }
+            LaracliError::PermissionDenied { action } => {
+                write!(f, "Permission denied, laracli could not {}", action)
+            }
+            LaracliError::NginxInvalid { output } => {
+                write!(f, "The Nginx configuration is invalid:\n{}", output.trim_end())
+            }
+            LaracliError::DownloadFailed { what, message } => {
+                write!(f, "Failed to download {}: {}", what, message)
+            }
+            LaracliError::NotFound(message) => write!(f, "{}", message),
+            LaracliError::ServiceUnavailable(message) => write!(f, "{}", message),
+            LaracliError::NotRunning { service } => write!(f, "{} is not running", service),
+            LaracliError::RolledBack {
+                step,
+                error,
+                undone,
+                not_undone,
+            } => {
+                write!(f, "Could not {}: {}", step, error)?;
+                for step in undone {
+                    write!(f, "\n  ↩ Undid: {}", step)?;
+                }
+                for step in not_undone {
+                    write!(f, "\n  ⚠ Could not undo: {}", step)?;
+                }
+                Ok(())
+            }
+            LaracliError::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for LaracliError {}
+
+// Errors of the helpers keep their kind when they already are a LaracliError
+impl From<Box<dyn Error>> for LaracliError {
+    fn from(error: Box<dyn Error>) -> Self {
+        match error.downcast::<LaracliError>() {
+            Ok(error) => *error,
+            Err(error) => LaracliError::Other(error.to_string()),
+        }
+    }
+}
+
+impl From<io::Error> for LaracliError {
+    fn from(error: io::Error) -> Self {
+        if error.kind() == io::ErrorKind::PermissionDenied {
+            LaracliError::PermissionDenied {
+                action: format!("complete the operation ({})", error),
+            }
+        } else {
+            LaracliError::Other(error.to_string())
+        }
+    }
+}
+
+// Exit code and hint of any error, io errors that refused access count as PermissionDenied
+pub fn exit_code(error: &(dyn Error + 'static)) -> i32 {
+    if let Some(error) = error.downcast_ref::<LaracliError>() {
+        return error.exit_code();
+    }
+    match error.downcast_ref::<io::Error>() {
+        Some(error) if error.kind() == io::ErrorKind::PermissionDenied => 6,
+        _ => 1,
+    }
+}
+
+pub fn hint(error: &(dyn Error + 'static)) -> Option<String> {
+    if let Some(error) = error.downcast_ref::<LaracliError>() {
+        return error.hint();
+    }
+    match error.downcast_ref::<io::Error>() {
+        Some(error) if error.kind() == io::ErrorKind::PermissionDenied => {
+            Some("Run the command again from an elevated terminal".to_string())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_codes_follow_the_error_kind() {
+        let missing: Box<dyn Error> = Box::new(LaracliError::ToolMissing {
+            tool: "Nginx",
+            path: PathBuf::from(r"C:\laracli\tools\nginx-1.23.3\nginx.exe"),
+        });
+        assert_eq!(exit_code(missing.as_ref()), 4);
+        assert!(hint(missing.as_ref()).unwrap().contains("laracli setup"));
+
+        let denied: Box<dyn Error> = Box::new(io::Error::from(io::ErrorKind::PermissionDenied));
+        assert_eq!(exit_code(denied.as_ref()), 6);
+
+        let other: Box<dyn Error> = "something else".into();
+        assert_eq!(exit_code(other.as_ref()), 1);
+        assert_eq!(hint(other.as_ref()), None);
+    }
+}
@@ -0,0 +1,315 @@
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+use flate2::read::GzDecoder;
+use indicatif::{ProgressBar, ProgressStyle};
+use xz2::read::XzDecoder;
+use zip::ZipArchive;
+
+use crate::helpers::plan::{self, Step};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+    TarXz,
+}
+
+impl ArchiveKind {
+    /// Guess the archive format from the file name
+    pub fn from_path(path: &Path) -> Option<ArchiveKind> {
+        let name = path.file_name()?.to_str()?.to_lowercase();
+        if name.ends_with(".zip") {
+            Some(ArchiveKind::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveKind::TarGz)
+        } else if name.ends_with(".tar.xz") || name.ends_with(".txz") {
+            Some(ArchiveKind::TarXz)
+        } else if name.ends_with(".tar") {
+            Some(ArchiveKind::Tar)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ExtractOptions {
+    /// Show a progress bar while extracting
+    pub progress: bool,
+    /// Label used in the progress bar
+    pub label: String,
+}
+
+/// Extract a zip, tar, tar.gz or tar.xz archive into `target`.
+/// Entries that would end up outside of `target` abort the extraction.
+/// Returns the number of files written.
+pub fn extract_archive(
+    archive: &Path,
+    target: &Path,
+    options: &ExtractOptions,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let kind = ArchiveKind::from_path(archive)
+        .ok_or_else(|| format!("Unsupported archive format: {}", archive.display()))?;
+    if plan::dry_run(Step::Extract { archive: archive.to_path_buf(), into: target.to_path_buf() }) {
+        return Ok(0);
+    }
+
+    fs::create_dir_all(target)?;
+
+    match kind {
+        ArchiveKind::Zip => extract_zip(archive, target, options),
+        ArchiveKind::Tar => extract_tar(File::open(archive)?, target, options),
+        ArchiveKind::TarGz => extract_tar(GzDecoder::new(File::open(archive)?), target, options),
+        ArchiveKind::TarXz => extract_tar(XzDecoder::new(File::open(archive)?), target, options),
+    }
+}
+
+/// Resolve an archive entry name to a path inside `target`.
+/// Returns `Ok(None)` for the archive root (`./`) and an error when the
+/// entry tries to escape the target directory.
+pub fn resolve_entry_path(
+    target: &Path,
+    entry_name: &Path,
+) -> Result<Option<PathBuf>, Box<dyn std::error::Error>> {
+    let mut relative = PathBuf::new();
+
+    for component in entry_name.components() {
+        match component {
+            Component::Normal(part) => relative.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(format!(
+                    "Refusing to extract {}: path escapes the target directory",
+                    entry_name.display()
+                )
+                .into());
+            }
+        }
+    }
+
+    if relative.as_os_str().is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(target.join(relative)))
+}
+
+// Lexically check that a relative path never walks above its starting directory
+fn stays_inside(relative: &Path) -> bool {
+    let mut depth: usize = 0;
+    for component in relative.components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if depth == 0 {
+                    return false;
+                }
+                depth -= 1;
+            }
+            Component::RootDir | Component::Prefix(_) => return false,
+        }
+    }
+    true
+}
+
+// Whether following `relative` from `start` passes through a symlink already on disk,
+// whose target the lexical check cannot see
+fn through_symlink(start: &Path, relative: &Path) -> bool {
+    let mut current = start.to_path_buf();
+    for component in relative.components() {
+        match component {
+            Component::Normal(part) => {
+                current.push(part);
+                if fs::symlink_metadata(&current).is_ok_and(|metadata| metadata.file_type().is_symlink()) {
+                    return true;
+                }
+            }
+            Component::ParentDir => {
+                current.pop();
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+fn new_progress_bar(len: Option<u64>, options: &ExtractOptions) -> ProgressBar {
+    if !options.progress {
+        return ProgressBar::hidden();
+    }
+
+    let pb = match len {
+        Some(len) => {
+            let pb = ProgressBar::new(len);
+            pb.set_style(
+                ProgressStyle::default_bar()
+                    .template("{spinner:.green} [{bar:40.cyan/blue}] {pos}/{len} {msg}")
+                    .unwrap(),
+            );
+            pb
+        }
+        None => {
+            let pb = ProgressBar::new_spinner();
+            pb.set_style(
+                ProgressStyle::default_spinner()
+                    .template("{spinner:.green} {pos} files - {msg}")
+                    .unwrap(),
+            );
+            pb.enable_steady_tick(Duration::from_millis(100));
+            pb
+        }
+    };
+    pb.set_message(format!("Extracting {}", options.label));
+    pb
+}
+
+fn extract_zip(
+    archive: &Path,
+    target: &Path,
+    options: &ExtractOptions,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut zip = ZipArchive::new(File::open(archive)?)?;
+    let pb = new_progress_bar(Some(zip.len() as u64), options);
+    let mut written = 0;
+
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i)?;
+        let entry_name = PathBuf::from(entry.name());
+        pb.inc(1);
+
+        let Some(dest) = resolve_entry_path(target, &entry_name)? else {
+            continue;
+        };
+
+        if entry.is_dir() {
+            fs::create_dir_all(&dest)?;
+            continue;
+        }
+
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut out = File::create(&dest)?;
+        io::copy(&mut entry, &mut out)?;
+        written += 1;
+    }
+
+    pb.finish_with_message(format!("✅ {} extracted", options.label));
+    Ok(written)
+}
+
+fn extract_tar<R: Read>(
+    reader: R,
+    target: &Path,
+    options: &ExtractOptions,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut archive = tar::Archive::new(reader);
+    let pb = new_progress_bar(None, options);
+    let mut written = 0;
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let entry_name = entry.path()?.into_owned();
+        pb.inc(1);
+
+        let Some(dest) = resolve_entry_path(target, &entry_name)? else {
+            continue;
+        };
+
+        let entry_type = entry.header().entry_type();
+        if entry_type.is_symlink() {
+            let link_name = entry
+                .link_name()?
+                .ok_or_else(|| format!("Link entry {} has no target", entry_name.display()))?
+                .into_owned();
+
+            // Symlinks are relative to the directory that contains them
+            let link_parent = dest.parent().unwrap_or(target);
+            let relative_parent = link_parent.strip_prefix(target).unwrap_or(Path::new(""));
+            if !stays_inside(&relative_parent.join(&link_name)) || through_symlink(link_parent, &link_name) {
+                return Err(format!(
+                    "Refusing to extract {}: link to {} escapes the target directory",
+                    entry_name.display(),
+                    link_name.display()
+                )
+                .into());
+            }
+        }
+
+        // Also refuses entries and hard link sources that leave `target` through a symlink
+        // extracted before them
+        if !entry.unpack_in(target)? {
+            return Err(format!(
+                "Refusing to extract {}: path escapes the target directory",
+                entry_name.display()
+            )
+            .into());
+        }
+        if entry_type.is_file() || entry_type.is_hard_link() {
+            written += 1;
+        }
+    }
+
+    pb.finish_with_message(format!("✅ {} extracted", options.label));
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_entry_path_rejects_traversal() {
+        let target = Path::new("tools");
+        assert!(resolve_entry_path(target, Path::new("../evil.dll")).is_err());
+        assert!(resolve_entry_path(target, Path::new("nginx/../../evil.dll")).is_err());
+        assert!(resolve_entry_path(target, Path::new("/etc/passwd")).is_err());
+    }
+
+    #[test]
+    fn test_resolve_entry_path() {
+        let target = Path::new("tools");
+        assert_eq!(
+            resolve_entry_path(target, Path::new("./conf/nginx.conf")).unwrap(),
+            Some(target.join("conf").join("nginx.conf"))
+        );
+        assert_eq!(resolve_entry_path(target, Path::new("./")).unwrap(), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_extract_tar_rejects_chained_symlinks() {
+        let dir = std::env::temp_dir().join(format!("laracli-archive-{}", std::process::id()));
+        let target = dir.join("tools").join("nginx");
+        fs::create_dir_all(&target).unwrap();
+
+        // `d -> .` keeps `d/d/../..` inside lexically, on disk it points two levels up
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, link) in [("d", "."), ("l", "d/d/../..")] {
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(tar::EntryType::Symlink);
+            header.set_size(0);
+            builder.append_link(&mut header, name, link).unwrap();
+        }
+        let mut header = tar::Header::new_gnu();
+        header.set_size(4);
+        builder.append_data(&mut header, "l/evil", &b"evil"[..]).unwrap();
+        let bytes = builder.into_inner().unwrap();
+
+        assert!(extract_tar(bytes.as_slice(), &target, &ExtractOptions::default()).is_err());
+        assert!(!dir.join("evil").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_stays_inside() {
+        assert!(stays_inside(Path::new("lib/../lib/libmysql.so")));
+        assert!(!stays_inside(Path::new("lib/../../libmysql.so")));
+    }
+}
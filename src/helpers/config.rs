@@ -1,32 +1,44 @@
-use std::{collections::HashSet, fs, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
 
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
 
+use crate::helpers::dns::DnsConfig;
+use crate::helpers::hosts;
+use crate::helpers::logger::LogConfig;
+use crate::helpers::network::NetworkConfig;
+use crate::helpers::project::ProjectType;
+use crate::helpers::watch_rules::WatchOptions;
+use crate::say;
+use crate::error::LaracliError;
+use crate::helpers::plan;
 
-pub fn create_config_file() {
-    println!("{}", "Creating config file".yellow());
+
+pub fn create_config_file() -> Result<(), LaracliError> {
+    say!("{}", "Creating config file".yellow());
     let config_path = PathBuf::from(r"C:\laracli\config.json");
-    let config_dir = config_path.parent().unwrap();
-    if !config_dir.exists() {
-        fs::create_dir_all(config_dir).expect("Failed to create config directory");
+    if let Some(config_dir) = config_path.parent()
+        && !config_dir.exists()
+    {
+        plan::create_dir_all(config_dir)
+            .map_err(|e| LaracliError::from_io("create the config directory", e))?;
     }
 
-    if !config_path.exists() {
-        let default_config = Config {
-            watched_paths: HashSet::new(),
-            linked_paths: HashSet::new(),
-        };
-
-        let config_json = serde_json::to_string_pretty(&default_config).unwrap();
-        fs::write(&config_path, config_json).expect("Failed to create config file");
+    if !plan::exists(&config_path) {
+        write_config_to(&config_path, &Config::default())?;
     }
-        println!("{}", "✅ Config file created".green());
-
+    say!("{}", "✅ Config file created".green());
+    Ok(())
 }
 
 pub fn get_config_path() -> PathBuf {    
-    
+    if let Some(home) = crate::helpers::path::home() {
+        return home.join("config.json");
+    }
+
     // Try system locations
     let system_locations = vec![
         PathBuf::from(r"C:\laracli\config.json"),
@@ -47,24 +59,109 @@ pub fn get_config_path() -> PathBuf {
 }
 
 // data in config.json
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Config {
     #[serde(default)]
     pub watched_paths: HashSet<String>,
     #[serde(default)]
     pub linked_paths: HashSet<String>,
+    // per linked path settings, keyed by the linked path
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub sites: HashMap<String, SiteConfig>,
+    // sites proxied to a local server, host name (without .test) -> target URL
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub proxies: HashMap<String, String>,
+    // dev processes (php, nginx, mysql) the daemon restarts when they stop
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub supervise: HashSet<String>,
+    // per watched path depth and include/exclude patterns, keyed by the watched path
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub watch_options: HashMap<String, WatchOptions>,
+    // built-in resolver for the site TLD, used instead of hosts entries when enabled
+    #[serde(default, skip_serializing_if = "DnsConfig::is_default")]
+    pub dns: DnsConfig,
+    // address sites are served on, shared by hosts entries, nginx and php-cgi
+    #[serde(default, skip_serializing_if = "NetworkConfig::is_default")]
+    pub network: NetworkConfig,
+    // level and rotation of the laracli log files
+    #[serde(default, skip_serializing_if = "LogConfig::is_default")]
+    pub logging: LogConfig,
+    // set APP_URL in the .env of projects auto-linked from watched paths
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub update_env: bool,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SiteConfig {
+    // host name (without .test) to use instead of the directory name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host_name: Option<String>,
+    // also serve every subdomain (*.name.test), resolved by the DNS resolver
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub wildcard: bool,
+    // subdomains registered in the hosts file, e.g. `acme` for acme.name.test
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subdomains: Vec<String>,
+    // project type to serve the path as instead of the detected one
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub kind: Option<ProjectType>,
+    // static sites: answer unknown paths with index.html for client-side routing
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub spa: bool,
+}
+
+impl Config {
+    // Host name (without .test) a linked path is served under
+    pub fn site_name(&self, path: &str) -> Option<String> {
+        site_name(&self.sites, path)
+    }
+
+    // Every host name (without .test) of a linked path: the site itself and its subdomains
+    pub fn host_names(&self, path: &str) -> Vec<String> {
+        let Some(name) = self.site_name(path) else {
+            return Vec::new();
+        };
+        let subdomains = self.sites.get(path).map(|site| site.subdomains.as_slice()).unwrap_or_default();
+        hosts::site_host_names(&name, subdomains)
+    }
+
+    // Linked path served under `name`
+    pub fn find_site(&self, name: &str) -> Option<String> {
+        let mut paths: Vec<&String> = self.linked_paths.iter().collect();
+        paths.sort();
+        paths
+            .into_iter()
+            .find(|path| self.site_name(path).is_some_and(|site| site.eq_ignore_ascii_case(name)))
+            .cloned()
+    }
+
+    // Options of a watched path, the defaults when none were set
+    pub fn watch_options(&self, path: &str) -> WatchOptions {
+        self.watch_options.get(path).cloned().unwrap_or_default()
+    }
+}
+
+// Host name for a linked path: the configured override, or the directory name
+pub fn site_name(sites: &HashMap<String, SiteConfig>, path: &str) -> Option<String> {
+    if let Some(host_name) = sites.get(path).and_then(|site| site.host_name.clone()) {
+        return Some(host_name);
+    }
+    Path::new(path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| name.to_string())
 }
 
 
 // Normalize path to remove Windows extended-length prefix
-fn normalize_path_string(path_str: &str) -> String {
+pub fn normalize_path_string(path_str: &str) -> String {
     let path = PathBuf::from(path_str);
 
     // Try to canonicalize and remove \\?\ prefix
     if let Ok(canonical) = path.canonicalize() {
         let canonical_str = canonical.to_string_lossy();
-        if canonical_str.starts_with("\\\\?\\") {
-            canonical_str[4..].to_string()
+        if let Some(stripped) = canonical_str.strip_prefix("\\\\?\\") {
+            stripped.to_string()
         } else {
             canonical_str.to_string()
         }
@@ -74,154 +171,222 @@ fn normalize_path_string(path_str: &str) -> String {
 }
 
 
-// Load config.json
-pub fn load_config() -> Config {
+// Read config.json without creating or rewriting it, used by the daemon
+pub fn read_config() -> Result<Config, LaracliError> {
     let path = get_config_path();
-    
-    // Ensure the directory exists
-    if let Some(parent) = path.parent() {
-        if !parent.exists() {
-            fs::create_dir_all(parent).expect("Failed to create config directory");
-        }
+    if !plan::exists(&path) {
+        return Ok(Config::default());
     }
-    
-    if path.exists() {
-        match fs::read_to_string(&path) {
-            Ok(contents) => {
-                match serde_json::from_str::<Config>(&contents) {
-                    Ok(mut config) => {
-                        // Normalize all paths in the config
-                        let normalized_paths: HashSet<String> = config
-                            .linked_paths
-                            .iter()
-                            .map(|p| normalize_path_string(p))
-                            .collect();
-                        
-                        // If paths were normalized, save the updated config
-                        if normalized_paths != config.linked_paths {
-                            config.linked_paths = normalized_paths;
-                            fs::write(&path, serde_json::to_string_pretty(&config).unwrap())
-                                .expect("Failed to save config");
-                            println!("✅ Normalized paths in config file");
-                        }
-                        
-                        config
-                    }
-                    Err(e) => {
-                        // Try to recover partial config
-                        let mut default_config = Config {
-                            watched_paths: HashSet::new(),
-                            linked_paths: HashSet::new(),
-                        };
-                        
-                        // Attempt to preserve watched_paths if they exist in the file
-                        if let Ok(partial) = serde_json::from_str::<serde_json::Value>(&contents) {
-                            if let Some(paths) = partial.get("watched_paths") {
-                                if let Ok(paths) = serde_json::from_value(paths.clone()) {
-                                    default_config.watched_paths = paths;
-                                }
-                            }
-                        }
-                        
-                        eprintln!("Failed to parse config file: {}. Creating new config with existing watched_paths.", e);
-                        fs::write(&path, serde_json::to_string_pretty(&default_config).unwrap())
-                            .expect("Failed to save config");
-                        default_config
-                    }
-                }
-            }
-            Err(e) => {
-                eprintln!("Failed to read config file: {}. Creating new config.", e);
-                let default_config = Config {
-                    watched_paths: HashSet::new(),
-                    linked_paths: HashSet::new(),
-                };
-                fs::write(&path, serde_json::to_string_pretty(&default_config).unwrap())
-                    .expect("Failed to save config");
-                default_config
-            }
-        }
-    } else {
-        println!("Config file doesn't exist. Creating new config at: {:?}", path);
-        let default_config = Config {
-            watched_paths: HashSet::new(),
-            linked_paths: HashSet::new(),
-        };
-        fs::write(&path, serde_json::to_string_pretty(&default_config).unwrap())
-            .expect("Failed to save config");
-        default_config
+    let contents = plan::read_to_string(&path).map_err(|e| LaracliError::ConfigError {
+        path: path.clone(),
+        message: e.to_string(),
+    })?;
+    serde_json::from_str(&contents).map_err(|e| LaracliError::ConfigError {
+        path: path.clone(),
+        message: e.to_string(),
+    })
+}
+
+// Save config.json, creating its directory when missing
+pub fn write_config(config: &Config) -> Result<(), LaracliError> {
+    let path = get_config_path();
+    if let Some(parent) = path.parent()
+        && !plan::exists(parent)
+    {
+        plan::create_dir_all(parent)
+            .map_err(|e| LaracliError::from_io("create the config directory", e))?;
     }
+    write_config_to(&path, config)
+}
+
+fn write_config_to(path: &Path, config: &Config) -> Result<(), LaracliError> {
+    let json = serde_json::to_string_pretty(config).map_err(|e| LaracliError::Other(e.to_string()))?;
+    plan::write(path, json).map_err(|e| LaracliError::from_io("save config.json", e))
+}
+
+// Load config.json, creating it when missing. A file that cannot be read or
+// parsed is an error and left alone, rewriting it would drop the sites in it.
+pub fn load_config() -> Result<Config, LaracliError> {
+    let path = get_config_path();
+    if !plan::exists(&path) {
+        say!("Config file doesn't exist. Creating new config at: {:?}", path);
+        let config = Config::default();
+        write_config(&config)?;
+        return Ok(config);
+    }
+
+    let mut config = read_config()?;
+    // Normalize all paths in the config, saving it when that changed them
+    let normalized_paths: HashSet<String> = config
+        .linked_paths
+        .iter()
+        .map(|p| normalize_path_string(p))
+        .collect();
+    if normalized_paths != config.linked_paths {
+        config.linked_paths = normalized_paths;
+        config.sites = config
+            .sites
+            .drain()
+            .map(|(p, site)| (normalize_path_string(&p), site))
+            .collect();
+        write_config_to(&path, &config)?;
+        say!("✅ Normalized paths in config file");
+    }
+    Ok(config)
 }
 
 // add a path to linked object in config.json
-pub fn add_to_linked_paths(path: &str) {
-    let mut config = load_config();
+pub fn add_to_linked_paths(path: &str) -> Result<(), LaracliError> {
+    let mut config = read_config()?;
+
+    let normalized_path = normalize_path_string(path);
+
+    if config.linked_paths.insert(normalized_path.clone()) {
+        say!("✅ Added path to linked_paths: {}", normalized_path);
+        write_config(&config)?;
+    } else {
+        say!("⚠️ Path already exists in linked_paths: {}", normalized_path);
+    }
+    Ok(())
+}
+
+// add a path to linked object in config.json, served under a custom host name
+pub fn add_to_linked_paths_with_name(path: &str, host_name: &str) -> Result<(), LaracliError> {
+    let mut config = read_config()?;
 
     let normalized_path = normalize_path_string(path);
 
+    config.sites.entry(normalized_path.clone()).or_default().host_name = Some(host_name.to_string());
     if config.linked_paths.insert(normalized_path.clone()) {
-        println!("✅ Added path to linked_paths: {}", normalized_path);
-
-        let config_path = get_config_path();
-        fs::write(
-            &config_path,
-            serde_json::to_string_pretty(&config).expect("Failed to serialize config"),
-        )
-        .expect("Failed to save config");
+        say!("✅ Added path to linked_paths: {} ({}.test)", normalized_path, host_name);
     } else {
-        println!("⚠️ Path already exists in linked_paths: {}", normalized_path);
+        say!("⚠️ Path already exists in linked_paths: {}", normalized_path);
     }
+    write_config(&config)
 }
 
 // Add a path to watched_paths in config.json
-pub fn add_to_watched_paths(path: &str) -> Result<(), String> {
-    let mut config = load_config();
+// Returns false when it was already watched
+pub fn add_to_watched_paths(path: &str) -> Result<bool, LaracliError> {
+    let mut config = read_config()?;
 
     let normalized_path = normalize_path_string(path);
 
-    if config.watched_paths.insert(normalized_path.clone()) {
-        let config_path = get_config_path();
-        fs::write(
-            &config_path,
-            serde_json::to_string_pretty(&config).expect("Failed to serialize config"),
-        )
-        .expect("Failed to save config");
-        Ok(())
+    if config.watched_paths.insert(normalized_path) {
+        write_config(&config)?;
+        Ok(true)
     } else {
-        Err(format!("Path already exists in watched_paths: {}", normalized_path))
+        Ok(false)
     }
 }
 
 // Remove a path from watched_paths in config.json
-pub fn remove_from_watched_paths(path: &str) -> Result<(), String> {
-    let mut config = load_config();
+pub fn remove_from_watched_paths(path: &str) -> Result<(), LaracliError> {
+    let mut config = read_config()?;
 
     let normalized_path = normalize_path_string(path);
+    config.watch_options.remove(&normalized_path);
     if config.watched_paths.remove(&normalized_path) {
-        let config_path = get_config_path();
-        fs::write(
-            &config_path,
-            serde_json::to_string_pretty(&config).expect("Failed to serialize config"),
-        )
-        .expect("Failed to save config");
-        Ok(())
+        write_config(&config)
     } else {
-        Err(format!("Path not found in watched_paths: {}", normalized_path))
+        Err(LaracliError::NotFound(format!("{} is not a watched directory", normalized_path)))
     }
 }
 
-pub fn remove_from_linked_paths(path: &str) -> Result<(), String> {
-    let mut config = load_config();
+pub fn remove_from_linked_paths(path: &str) -> Result<(), LaracliError> {
+    let mut config = read_config()?;
     let normalized_path = normalize_path_string(path);
+    config.sites.remove(&normalized_path);
     if config.linked_paths.remove(&normalized_path) {
-        let config_path = get_config_path();
-        fs::write(
-            &config_path,
-            serde_json::to_string_pretty(&config).expect("Failed to serialize config"),
-        )
-        .expect("Failed to save config");
+        write_config(&config)
+    } else {
+        Err(LaracliError::NotFound(format!("{} is not a linked path", normalized_path)))
+    }
+}
+
+// Replace the processes the daemon keeps alive (`php`, `nginx`, `mysql`)
+pub fn set_supervised_processes(processes: &[&str]) -> Result<(), LaracliError> {
+    let mut config = read_config()?;
+    config.supervise = processes.iter().map(|process| process.to_string()).collect();
+    write_config(&config)
+}
+
+// Store the depth and patterns of a watched path, the defaults are not written
+pub fn set_watch_options(path: &str, options: WatchOptions) -> Result<(), LaracliError> {
+    let mut config = read_config()?;
+    let normalized_path = normalize_path_string(path);
+    if options == WatchOptions::default() {
+        config.watch_options.remove(&normalized_path);
+    } else {
+        config.watch_options.insert(normalized_path, options);
+    }
+    write_config(&config)
+}
+
+// Change the settings of a linked path, settings left at their defaults are not written
+pub fn update_site(path: &str, update: impl FnOnce(&mut SiteConfig)) -> Result<(), LaracliError> {
+    let mut config = read_config()?;
+    let normalized_path = normalize_path_string(path);
+    let site = config.sites.entry(normalized_path.clone()).or_default();
+    update(site);
+    if *site == SiteConfig::default() {
+        config.sites.remove(&normalized_path);
+    }
+    write_config(&config)
+}
+
+// Register a subdomain of the site served under `name`, returns its linked path
+pub fn add_subdomain(name: &str, subdomain: &str) -> Result<String, LaracliError> {
+    let mut config = read_config()?;
+    let path = config
+        .find_site(name)
+        .ok_or_else(|| LaracliError::NotFound(format!("No linked site is served as {}.test", name)))?;
+    let site = config.sites.entry(path.clone()).or_default();
+    if site.subdomains.iter().any(|existing| existing == subdomain) {
+        return Err(LaracliError::InvalidArgument(format!("{}.{}.test already exists", subdomain, name)));
+    }
+    site.subdomains.push(subdomain.to_string());
+    site.subdomains.sort();
+    write_config(&config)?;
+    Ok(path)
+}
+
+// Remove a registered subdomain of the site served under `name`, returns its linked path
+pub fn remove_subdomain(name: &str, subdomain: &str) -> Result<String, LaracliError> {
+    let mut config = read_config()?;
+    let path = config
+        .find_site(name)
+        .ok_or_else(|| LaracliError::NotFound(format!("No linked site is served as {}.test", name)))?;
+    let site = config.sites.entry(path.clone()).or_default();
+    let count = site.subdomains.len();
+    site.subdomains.retain(|existing| existing != subdomain);
+    if site.subdomains.len() == count {
+        return Err(LaracliError::NotFound(format!("{}.{}.test is not registered", subdomain, name)));
+    }
+    if *site == SiteConfig::default() {
+        config.sites.remove(&path);
+    }
+    write_config(&config)?;
+    Ok(path)
+}
+
+// Serve `name`.test by proxying to `target`, replaces the target of an existing proxy
+pub fn add_proxy(name: &str, target: &str) -> Result<(), LaracliError> {
+    let mut config = read_config()?;
+    if let Some(path) = config.find_site(name) {
+        return Err(LaracliError::InvalidArgument(format!("{}.test is already served from {}", name, path)));
+    }
+    config.proxies.insert(name.to_string(), target.to_string());
+    write_config(&config)?;
+    Ok(())
+}
+
+pub fn remove_proxy(name: &str) -> Result<(), LaracliError> {
+    let mut config = read_config()?;
+    if config.proxies.remove(name).is_some() {
+        write_config(&config)?;
         Ok(())
     } else {
-        Err(format!("Path not found in linked_paths: {}", normalized_path))
+        Err(LaracliError::NotFound(format!("No proxy named {}", name)))
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,266 @@
+//! A tiny DNS server answering every name under the site TLD with the
+//! addresses sites are served on, so sites resolve without hosts file
+//! entries. Other names are refused, nothing is forwarded.
+
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+pub const TYPE_A: u16 = 1;
+pub const TYPE_AAAA: u16 = 28;
+const TYPE_ANY: u16 = 255;
+const CLASS_IN: u16 = 1;
+const RCODE_FORMAT_ERROR: u8 = 1;
+const RCODE_REFUSED: u8 = 5;
+// Short TTL so disabling the resolver takes effect quickly
+const TTL: u32 = 60;
+/// TLD of every site, hosts entries and nginx server names use it too
+pub const SITE_TLD: &str = "test";
+
+/// `dns` entry of config.json
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DnsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_port")]
+    pub port: u16,
+}
+
+fn default_port() -> u16 {
+    53
+}
+
+impl Default for DnsConfig {
+    fn default() -> Self {
+        DnsConfig {
+            enabled: false,
+            port: default_port(),
+        }
+    }
+}
+
+impl DnsConfig {
+    pub fn is_default(&self) -> bool {
+        *self == DnsConfig::default()
+    }
+}
+
+struct Question {
+    name: String,
+    qtype: u16,
+    qclass: u16,
+    // Offset of the end of the question section
+    end: usize,
+}
+
+// Read the first question of a query, compression is not expected in questions
+fn parse_question(packet: &[u8]) -> Option<Question> {
+    if packet.len() < 12 || u16::from_be_bytes([packet[4], packet[5]]) == 0 {
+        return None;
+    }
+
+    let mut labels = Vec::new();
+    let mut pos = 12;
+    loop {
+        let len = *packet.get(pos)? as usize;
+        pos += 1;
+        if len == 0 {
+            break;
+        }
+        if len & 0xC0 != 0 {
+            return None;
+        }
+        let label = packet.get(pos..pos + len)?;
+        labels.push(String::from_utf8_lossy(label).to_lowercase());
+        pos += len;
+    }
+
+    let qtype = u16::from_be_bytes([*packet.get(pos)?, *packet.get(pos + 1)?]);
+    let qclass = u16::from_be_bytes([*packet.get(pos + 2)?, *packet.get(pos + 3)?]);
+    Some(Question {
+        name: labels.join("."),
+        qtype,
+        qclass,
+        end: pos + 4,
+    })
+}
+
+fn in_zone(name: &str, tld: &str) -> bool {
+    let tld = tld.trim_matches('.').to_lowercase();
+    name == tld || name.ends_with(&format!(".{}", tld))
+}
+
+/// Build the reply to one query packet answering with `addresses`, `None` when it is not worth answering
+pub fn build_response(query: &[u8], tld: &str, addresses: &[IpAddr]) -> Option<Vec<u8>> {
+    if query.len() < 12 || query[2] & 0x80 != 0 {
+        // Too short, or a response rather than a query
+        return None;
+    }
+
+    let Some(question) = parse_question(query) else {
+        let mut response = query[..12].to_vec();
+        response[2] = 0x80 | (query[2] & 0x01);
+        response[3] = RCODE_FORMAT_ERROR;
+        response[4..12].fill(0);
+        return Some(response);
+    };
+
+    let mut answers: Vec<IpAddr> = Vec::new();
+    let rcode = if !in_zone(&question.name, tld) || question.qclass != CLASS_IN {
+        RCODE_REFUSED
+    } else {
+        answers.extend(addresses.iter().filter(|address| match address {
+            IpAddr::V4(_) => matches!(question.qtype, TYPE_A | TYPE_ANY),
+            IpAddr::V6(_) => matches!(question.qtype, TYPE_AAAA | TYPE_ANY),
+        }));
+        0
+    };
+
+    let mut response = Vec::with_capacity(question.end + answers.len() * 28);
+    response.extend_from_slice(&query[..2]); // id
+    // QR + opcode + AA, keep RD from the query
+    response.push(0x84 | (query[2] & 0x01));
+    response.push(rcode);
+    response.extend_from_slice(&1u16.to_be_bytes()); // questions
+    response.extend_from_slice(&(answers.len() as u16).to_be_bytes());
+    response.extend_from_slice(&0u16.to_be_bytes()); // authority
+    response.extend_from_slice(&0u16.to_be_bytes()); // additional
+    response.extend_from_slice(&query[12..question.end]);
+
+    for address in answers {
+        response.extend_from_slice(&[0xC0, 0x0C]); // pointer to the question name
+        let (rtype, data) = match address {
+            IpAddr::V4(ip) => (TYPE_A, ip.octets().to_vec()),
+            IpAddr::V6(ip) => (TYPE_AAAA, ip.octets().to_vec()),
+        };
+        response.extend_from_slice(&rtype.to_be_bytes());
+        response.extend_from_slice(&CLASS_IN.to_be_bytes());
+        response.extend_from_slice(&TTL.to_be_bytes());
+        response.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        response.extend_from_slice(&data);
+    }
+
+    Some(response)
+}
+
+/// Answer queries on `socket` until `running` is cleared, counting them in `queries`
+pub fn serve(
+    socket: UdpSocket,
+    tld: &str,
+    addresses: &[IpAddr],
+    running: Arc<AtomicBool>,
+    queries: Arc<AtomicU64>,
+) -> io::Result<()> {
+    socket.set_read_timeout(Some(Duration::from_secs(1)))?;
+    let mut buf = [0u8; 512];
+
+    while running.load(Ordering::SeqCst) {
+        let (len, peer) = match socket.recv_from(&mut buf) {
+            Ok(received) => received,
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => continue,
+            // Windows reports ICMP port unreachable of earlier replies as an error
+            Err(e) if e.kind() == io::ErrorKind::ConnectionReset => continue,
+            Err(e) => return Err(e),
+        };
+        queries.fetch_add(1, Ordering::Relaxed);
+        if let Some(response) = build_response(&buf[..len], tld, addresses) {
+            let _ = socket.send_to(&response, peer);
+        }
+    }
+
+    Ok(())
+}
+
+/// Ask the resolver at `server` for the addresses of `name`
+pub fn query(server: SocketAddr, name: &str, qtype: u16) -> Result<Vec<IpAddr>, Box<dyn std::error::Error>> {
+    let id: u16 = (std::process::id() & 0xFFFF) as u16;
+    let mut packet = Vec::new();
+    packet.extend_from_slice(&id.to_be_bytes());
+    packet.extend_from_slice(&[0x01, 0x00]); // recursion desired
+    packet.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]);
+    for label in name.trim_end_matches('.').split('.') {
+        packet.push(label.len() as u8);
+        packet.extend_from_slice(label.as_bytes());
+    }
+    packet.push(0);
+    packet.extend_from_slice(&qtype.to_be_bytes());
+    packet.extend_from_slice(&CLASS_IN.to_be_bytes());
+
+    let bind: SocketAddr = if server.is_ipv4() {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (Ipv6Addr::UNSPECIFIED, 0).into()
+    };
+    let socket = UdpSocket::bind(bind)?;
+    socket.set_read_timeout(Some(Duration::from_secs(2)))?;
+    socket.send_to(&packet, server)?;
+
+    let mut buf = [0u8; 512];
+    let (len, _) = socket.recv_from(&mut buf)?;
+    let response = &buf[..len];
+    if len < 12 || response[..2] != id.to_be_bytes() {
+        return Err("Invalid DNS response".into());
+    }
+    match response[3] & 0x0F {
+        0 => {}
+        RCODE_REFUSED => return Err(format!("{} was refused", name).into()),
+        rcode => return Err(format!("DNS error code {}", rcode).into()),
+    }
+
+    let question = parse_question(response).ok_or("Invalid DNS response")?;
+    let answer_count = u16::from_be_bytes([response[6], response[7]]);
+    let mut pos = question.end;
+    let mut addresses = Vec::new();
+    for _ in 0..answer_count {
+        // Our answers always use a 2 byte name pointer
+        let header = response.get(pos + 2..pos + 12).ok_or("Truncated DNS answer")?;
+        let rtype = u16::from_be_bytes([header[0], header[1]]);
+        let data_len = u16::from_be_bytes([header[8], header[9]]) as usize;
+        let data = response.get(pos + 12..pos + 12 + data_len).ok_or("Truncated DNS answer")?;
+        match (rtype, data.len()) {
+            (TYPE_A, 4) => addresses.push(IpAddr::from(<[u8; 4]>::try_from(data)?)),
+            (TYPE_AAAA, 16) => addresses.push(IpAddr::from(<[u8; 16]>::try_from(data)?)),
+            _ => {}
+        }
+        pos += 12 + data_len;
+    }
+    Ok(addresses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_resolver_answers_site_tld_only() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let server = socket.local_addr().unwrap();
+        let running = Arc::new(AtomicBool::new(true));
+        let queries = Arc::new(AtomicU64::new(0));
+
+        let thread_running = running.clone();
+        let thread_queries = queries.clone();
+        let addresses = vec![IpAddr::V4(Ipv4Addr::LOCALHOST), IpAddr::V6(Ipv6Addr::LOCALHOST)];
+        let handle =
+            thread::spawn(move || serve(socket, "test", &addresses, thread_running, thread_queries));
+
+        assert_eq!(
+            query(server, "blog.test", TYPE_A).unwrap(),
+            vec![IpAddr::V4(Ipv4Addr::LOCALHOST)]
+        );
+        assert_eq!(
+            query(server, "acme.shop.TEST", TYPE_AAAA).unwrap(),
+            vec![IpAddr::V6(Ipv6Addr::LOCALHOST)]
+        );
+        assert!(query(server, "example.com", TYPE_A).is_err());
+        assert_eq!(queries.load(Ordering::Relaxed), 3);
+
+        running.store(false, Ordering::SeqCst);
+        handle.join().unwrap().unwrap();
+    }
+}
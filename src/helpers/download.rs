@@ -8,6 +8,9 @@ use std::{
     time::Duration,
 };
 use tokio::time::sleep;
+use crate::say;
+use crate::error::LaracliError;
+use crate::helpers::plan::{self, Step};
 
 pub async fn download_with_progress_async(
     url: &str,
@@ -15,8 +18,12 @@ pub async fn download_with_progress_async(
     label: &str,
     max_retries: usize,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if plan::dry_run(Step::Download { url: url.to_string(), path: out_path.into() }) {
+        return Ok(());
+    }
+
     for attempt in 1..=max_retries {
-        println!(
+        say!(
             "{}",
             format!("Downloading {} (Attempt {}/{})", label, attempt, max_retries).yellow()
         );
@@ -26,7 +33,7 @@ pub async fn download_with_progress_async(
         if Path::new(out_path).exists() {
             resume_from = std::fs::metadata(out_path).map(|m| m.len()).unwrap_or(0);
             if resume_from > 0 {
-                println!("Resuming download from {} bytes", resume_from);
+                say!("Resuming download from {} bytes", resume_from);
             }
         }
 
@@ -94,22 +101,30 @@ pub async fn download_with_progress_async(
         match result {
             Ok(Ok(())) => return Ok(()),
             Ok(Err(e)) => {
-                println!("❌ Download failed: {}", e);
+                say!("❌ Download failed: {}", e);
                 if attempt == max_retries {
-                    return Err(e);
+                    return Err(LaracliError::DownloadFailed {
+                        what: label.to_string(),
+                        message: e.to_string(),
+                    }
+                    .into());
                 }
             }
             Err(e) => {
-                println!("❌ Task join error: {}", e);
+                say!("❌ Task join error: {}", e);
                 if attempt == max_retries {
                     return Err(format!("Background task failed: {}", e).into());
                 }
             }
         }
 
-        println!("Retrying in 10 seconds...");
+        say!("Retrying in 10 seconds...");
         sleep(Duration::from_secs(10)).await;
     }
 
-    Err("Download failed after all retries".into())
+    Err(LaracliError::DownloadFailed {
+        what: label.to_string(),
+        message: format!("no attempt succeeded out of {}", max_retries),
+    }
+    .into())
 }
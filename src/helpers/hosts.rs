@@ -1,85 +1,309 @@
-use std::{fs::{self, OpenOptions}, io::BufReader};
-use std::io::{Write, BufRead};
-use std::fs::File;
+use std::fs;
+use std::collections::HashSet;
+use std::net::IpAddr;
 
+use crate::helpers::config;
+use crate::helpers::journal::{self, EventKind};
+use crate::say;
+use crate::error::LaracliError;
+use crate::helpers::plan;
+
+const HOSTS_PATH: &str = r"C:\Windows\System32\drivers\etc\hosts";
+
+// The built-in DNS resolver answers for every site, the hosts file is left alone
+fn dns_enabled() -> bool {
+    config::read_config().is_ok_and(|config| config.dns.enabled)
+}
+
+// Addresses every site entry points to, IPv4 and ::1 by default
+fn host_addresses() -> Vec<IpAddr> {
+    config::read_config()
+        .map(|config| config.network)
+        .unwrap_or_default()
+        .host_addresses()
+}
 
 pub fn add_host_entry(project_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if dns_enabled() {
+        return Ok(());
+    }
 
     // elevate();
     // if !elevate::is_elevated() {
-    //     println!("🔒 Elevation required to modify hosts file. Requesting UAC permission...");
+    //     say!("🔒 Elevation required to modify hosts file. Requesting UAC permission...");
     //     elevate::run_as_admin()?;
     //     return Ok(());
     // }
 
-    let entry = format!("127.0.0.1\t{}.test #added by laracli", project_name);
-    let hosts_path = r"C:\Windows\System32\drivers\etc\hosts";
-
-    // Check if entry already exists
-    let file = fs::File::open(hosts_path)?;
-    let reader = BufReader::new(file);
-
-    for line in reader.lines() {
-        let line = line?;
-        if line.contains(&format!("{}.test", project_name)) {
-            println!("Host entry for {}.test already exists.", project_name);
-            return Ok(());
-        }
+    // Check if the entries already exist
+    let contents = plan::read_to_string(HOSTS_PATH)?;
+    let updated = apply_host_changes(&contents, &host_addresses(), &[project_name.to_string()], &[]);
+    if updated == contents {
+        say!("Host entry for {}.test already exists.", project_name);
+        return Ok(());
     }
 
-    // Add new entry
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(hosts_path)?;
-
-    writeln!(file, "{}", entry)?;
-    println!("✅ Added host entry for {}.test", project_name);
+    write_hosts(&contents, &updated)?;
+    say!("✅ Added host entry for {}.test", project_name);
     Ok(())
 }
 
 
 pub fn remove_host_entry(project_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if dns_enabled() {
+        return Ok(());
+    }
 
     // elevate();
     // if !elevate::is_elevated() {
-    //     println!("🔒 Elevation required to modify hosts file. Requesting UAC permission...");
+    //     say!("🔒 Elevation required to modify hosts file. Requesting UAC permission...");
     //     elevate::run_as_admin()?;
     //     return Ok(());
     // }
-    let hosts_path = r"C:\Windows\System32\drivers\etc\hosts";
-    let temp_hosts_path = r"C:\laracli\hosts.tmp";
-
-    // Read the current hosts file
-    let input_file = File::open(hosts_path)?;
-    let reader = BufReader::new(input_file);
 
-    // Write all lines except the one with our project name into a temporary file
-    let mut output_file = OpenOptions::new()
-        .create(true)
-        .write(true)
-        .truncate(true)
-        .open(temp_hosts_path)?;
+    // Only the laracli entries of exactly this name, `myblog.test` stays when removing `blog`
+    let contents = plan::read_to_string(HOSTS_PATH)?;
+    let updated = apply_host_changes(&contents, &host_addresses(), &[], &[project_name.to_string()]);
+    if updated == contents {
+        say!("Host entry for {}.test not found.", project_name);
+        return Ok(());
+    }
 
-    let mut found = false;
+    write_hosts(&contents, &updated)?;
+    say!("🔒 Removed host entry for {}.test", project_name);
+    Ok(())
+}
 
-    for line in reader.lines() {
-        let line = line?;
-        if !line.contains(&format!("{}.test", project_name)) {
-            writeln!(output_file, "{}", line)?;
+// Remove every line that laracli added to the hosts file, returns how many were removed
+pub fn remove_laracli_host_entries() -> Result<usize, Box<dyn std::error::Error>> {
+    let contents = plan::read_to_string(HOSTS_PATH)?;
+    let mut updated = String::new();
+    let mut removed = 0;
+    for line in contents.lines() {
+        if line.contains("#added by laracli") {
+            removed += 1;
         } else {
-            found = true;
-            println!("🔒 Removed host entry for {}.test", project_name);
+            updated.push_str(line);
+            updated.push('\n');
         }
     }
 
-    // Replace original hosts file with the updated one
-    std::fs::copy(temp_hosts_path, hosts_path)?;
-    std::fs::remove_file(temp_hosts_path)?; // Clean up temporary file
+    if removed > 0 {
+        write_hosts(&contents, &updated)?;
+    }
+    Ok(removed)
+}
+
+// Journal the names whose entries appeared or disappeared in a rewrite
+fn record_host_changes(before: &str, after: &str) {
+    let before = parse_laracli_host_names(before);
+    let after = parse_laracli_host_names(after);
+    for name in after.difference(&before) {
+        journal::record(EventKind::HostAdded, name, "");
+    }
+    for name in before.difference(&after) {
+        journal::record(EventKind::HostRemoved, name, "");
+    }
+}
+
+// A single DNS label: letters, digits and inner hyphens
+pub fn is_dns_label(label: &str) -> bool {
+    !label.is_empty()
+        && label.len() <= 63
+        && !label.starts_with('-')
+        && !label.ends_with('-')
+        && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+// A label of a site name. Folders like `my_app` were served before names were checked,
+// underscores stay allowed for them
+fn is_host_label(label: &str) -> bool {
+    is_dns_label(&label.replace('_', "a"))
+}
 
-    if !found {
-        println!("Host entry for {}.test not found.", project_name);
+/// Check a host name without .test, like `blog`, `my_app` or `acme.shop`, before it goes into the hosts file
+pub fn validate_host_name(name: &str) -> Result<(), LaracliError> {
+    if name.len() <= 253 && name.split('.').all(is_host_label) {
+        return Ok(());
     }
+    Err(LaracliError::InvalidArgument(format!(
+        "{}.test is not a valid host name, use letters, digits, underscores and inner hyphens",
+        name
+    )))
+}
+
+/// Host names (without .test) of a site and its registered subdomains, like `shop` and `api.shop`
+pub fn site_host_names(name: &str, subdomains: &[String]) -> Vec<String> {
+    std::iter::once(name.to_string())
+        .chain(subdomains.iter().map(|subdomain| format!("{}.{}", subdomain, name)))
+        .collect()
+}
+
+// Names (without .test) of the entries laracli added to the hosts file
+pub fn laracli_host_names() -> Result<HashSet<String>, Box<dyn std::error::Error>> {
+    let contents = fs::read_to_string(r"C:\Windows\System32\drivers\etc\hosts")?;
+    Ok(parse_laracli_host_names(&contents))
+}
+
+pub fn parse_laracli_host_names(contents: &str) -> HashSet<String> {
+    contents
+        .lines()
+        .filter(|line| line.contains("#added by laracli"))
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .filter_map(|host| host.strip_suffix(".test"))
+        .map(|name| name.to_string())
+        .collect()
+}
 
+// Add and remove several laracli entries with a single rewrite of the hosts file
+pub fn update_host_entries(add: &[String], remove: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    if (add.is_empty() && remove.is_empty()) || dns_enabled() {
+        return Ok(());
+    }
+
+    let contents = plan::read_to_string(HOSTS_PATH)?;
+    let updated = apply_host_changes(&contents, &host_addresses(), add, remove);
+    if updated == contents {
+        return Ok(());
+    }
+
+    write_hosts(&contents, &updated)
+}
+
+// Replace the hosts file through a temporary copy and journal the entries that changed
+fn write_hosts(contents: &str, updated: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if plan::is_dry_run() {
+        plan::write(HOSTS_PATH, updated)?;
+        return Ok(());
+    }
+
+    let temp_hosts_path = r"C:\laracli\hosts.tmp";
+    fs::write(temp_hosts_path, updated)?;
+    let copied = fs::copy(temp_hosts_path, HOSTS_PATH);
+    let _ = fs::remove_file(temp_hosts_path);
+    copied.map_err(|e| LaracliError::from_io("write the hosts file", e))?;
+    record_host_changes(contents, updated);
     Ok(())
 }
+
+// Names whose laracli entries do not point to exactly `addresses`, e.g. after the bind address changed
+pub fn outdated_laracli_host_names() -> Result<HashSet<String>, Box<dyn std::error::Error>> {
+    let contents = fs::read_to_string(r"C:\Windows\System32\drivers\etc\hosts")?;
+    Ok(parse_outdated_host_names(&contents, &host_addresses()))
+}
+
+pub fn parse_outdated_host_names(contents: &str, addresses: &[IpAddr]) -> HashSet<String> {
+    let entries = laracli_entries(contents);
+    entries
+        .iter()
+        .map(|(_, host)| host)
+        .filter(|host| {
+            let pointed: HashSet<&String> = entries
+                .iter()
+                .filter(|(_, other)| other == *host)
+                .map(|(address, _)| address)
+                .collect();
+            pointed.len() != addresses.len()
+                || addresses.iter().any(|address| !pointed.contains(&address.to_string()))
+        })
+        .filter_map(|host| host.strip_suffix(".test"))
+        .map(|name| name.to_string())
+        .collect()
+}
+
+// (address, lowercase host) of a hosts file line
+fn entry_fields(line: &str) -> Option<(String, String)> {
+    let mut fields = line.split_whitespace();
+    Some((fields.next()?.to_string(), fields.next()?.to_lowercase()))
+}
+
+// Entries of every line laracli added
+fn laracli_entries(contents: &str) -> Vec<(String, String)> {
+    contents
+        .lines()
+        .filter(|line| line.contains("#added by laracli"))
+        .filter_map(entry_fields)
+        .collect()
+}
+
+pub fn apply_host_changes(contents: &str, addresses: &[IpAddr], add: &[String], remove: &[String]) -> String {
+    let removed: HashSet<String> = remove.iter().map(|name| format!("{}.test", name).to_lowercase()).collect();
+    let added: HashSet<String> = add.iter().map(|name| format!("{}.test", name).to_lowercase()).collect();
+    let wanted_addresses: HashSet<String> = addresses.iter().map(|address| address.to_string()).collect();
+
+    // Removed hosts go, added hosts keep only the entries for the current addresses
+    let mut lines: Vec<&str> = contents
+        .lines()
+        .filter(|line| {
+            !(line.contains("#added by laracli")
+                && entry_fields(line).is_some_and(|(address, host)| {
+                    removed.contains(&host)
+                        || (added.contains(&host) && !wanted_addresses.contains(&address))
+                }))
+        })
+        .collect();
+
+    let existing: HashSet<(String, String)> = lines.iter().filter_map(|line| entry_fields(line)).collect();
+    let new_lines: Vec<String> = add
+        .iter()
+        .flat_map(|name| addresses.iter().map(move |address| (address.to_string(), name)))
+        .filter(|(address, name)| !existing.contains(&(address.clone(), format!("{}.test", name).to_lowercase())))
+        .map(|(address, name)| format!("{}\t{}.test #added by laracli", address, name))
+        .collect();
+    lines.extend(new_lines.iter().map(|line| line.as_str()));
+
+    let mut updated = lines.join("\n");
+    if !updated.is_empty() {
+        updated.push('\n');
+    }
+    updated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_host_changes() {
+        let contents = "127.0.0.1\tlocalhost\n127.0.0.1\tblog.test #added by laracli\n127.0.0.1\tmyblog.test #added by laracli\n";
+        let updated = apply_host_changes(
+            contents,
+            &["127.0.0.1".parse().unwrap()],
+            &["shop".to_string(), "myblog".to_string()],
+            &["blog".to_string()],
+        );
+        assert_eq!(
+            updated,
+            "127.0.0.1\tlocalhost\n127.0.0.1\tmyblog.test #added by laracli\n127.0.0.1\tshop.test #added by laracli\n"
+        );
+    }
+
+    #[test]
+    fn test_host_entries_follow_addresses() {
+        let addresses: Vec<IpAddr> = vec!["127.0.0.1".parse().unwrap(), "::1".parse().unwrap()];
+        let contents = "127.0.0.1\tblog.test #added by laracli\n10.0.0.5\tshop.test #added by laracli\n";
+        assert_eq!(
+            parse_outdated_host_names(contents, &addresses),
+            HashSet::from(["blog".to_string(), "shop".to_string()])
+        );
+
+        let updated = apply_host_changes(contents, &addresses, &["blog".to_string(), "shop".to_string()], &[]);
+        assert_eq!(
+            updated,
+            "127.0.0.1\tblog.test #added by laracli\n::1\tblog.test #added by laracli\n127.0.0.1\tshop.test #added by laracli\n::1\tshop.test #added by laracli\n"
+        );
+        assert!(parse_outdated_host_names(&updated, &addresses).is_empty());
+    }
+
+    #[test]
+    fn test_validate_host_name() {
+        assert!(validate_host_name("blog").is_ok());
+        assert!(validate_host_name("api.my-shop").is_ok());
+        assert!(validate_host_name("my_app").is_ok());
+        assert!(validate_host_name("My Project").is_err());
+        assert!(validate_host_name("").is_err());
+        assert!(validate_host_name("-blog").is_err());
+        assert!(validate_host_name("blog..shop").is_err());
+        assert!(validate_host_name("evil.test\n1.2.3.4\tbank").is_err());
+    }
+}
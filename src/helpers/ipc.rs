@@ -0,0 +1,316 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt};
+
+/// Endpoint of the laracli daemon (`laracli` service)
+pub const DAEMON_ENDPOINT: &str = "laracli";
+
+// Longest `send` waits for a reply, a hung service must not hang the CLI
+const REPLY_TIMEOUT: Duration = Duration::from_secs(30);
+
+// What clients may do with the pipe: FILE_GENERIC_READ | FILE_WRITE_DATA, so not
+// FILE_CREATE_PIPE_INSTANCE which GENERIC_WRITE includes
+#[cfg(windows)]
+const PIPE_CLIENT_ACCESS: u32 = 0x0012_008b;
+
+/// A command sent from the CLI to a background service.
+/// Sent as a single JSON line, e.g. `{"command":"add-site","path":"C:\\www\\blog","name":"blog"}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum Request {
+    /// Re-read config.json and apply the differences
+    ReloadConfig,
+    /// Add the host entry of a site and reload nginx. `path` is the linked folder, proxies have none
+    AddSite {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        path: Option<String>,
+        name: String,
+    },
+    /// Remove the host entries of a site and its registered `subdomains` and reload nginx
+    RemoveSite {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        path: Option<String>,
+        name: String,
+        #[serde(default)]
+        subdomains: Vec<String>,
+    },
+    /// Compare config.json with nginx, hosts and the linked directories and fix the differences
+    Sync {
+        #[serde(default)]
+        dry_run: bool,
+    },
+    /// Report what the service is doing
+    Status,
+    /// Hold config reloads and reconciles for at most `seconds` while the CLI changes several files
+    Pause { seconds: u64 },
+    /// End a pause and apply the config changes made during it
+    Resume,
+}
+
+/// Reply of a service, sent back as a single JSON line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Response {
+    pub ok: bool,
+    pub message: String,
+    #[serde(default)]
+    pub data: serde_json::Value,
+}
+
+impl Response {
+    pub fn ok(message: impl Into<String>) -> Response {
+        Response {
+            ok: true,
+            message: message.into(),
+            data: serde_json::Value::Null,
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Response {
+        Response {
+            ok: false,
+            message: message.into(),
+            data: serde_json::Value::Null,
+        }
+    }
+
+    pub fn with_data(mut self, data: serde_json::Value) -> Response {
+        self.data = data;
+        self
+    }
+}
+
+/// Platform address of an endpoint: a named pipe on Windows, a Unix socket elsewhere
+pub fn endpoint_address(name: &str) -> String {
+    #[cfg(windows)]
+    {
+        format!(r"\\.\pipe\{}", name)
+    }
+    #[cfg(not(windows))]
+    {
+        std::env::temp_dir()
+            .join(format!("{}.sock", name))
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+/// Send a request to a running service and wait for its reply
+pub fn send(name: &str, request: &Request) -> Result<Response, Box<dyn std::error::Error>> {
+    let address = endpoint_address(name);
+    let mut payload = serde_json::to_string(request)?;
+    payload.push('\n');
+
+    #[cfg(windows)]
+    let stream = {
+        use std::os::windows::fs::OpenOptionsExt;
+
+        const ERROR_PIPE_BUSY: i32 = 231;
+        let mut attempts = 0;
+        loop {
+            match std::fs::OpenOptions::new().access_mode(PIPE_CLIENT_ACCESS).open(&address) {
+                Ok(file) => break file,
+                Err(e) if e.raw_os_error() == Some(ERROR_PIPE_BUSY) && attempts < 10 => {
+                    attempts += 1;
+                    std::thread::sleep(Duration::from_millis(100));
+                }
+                Err(e) => {
+                    return Err(format!("Service endpoint {} is not available: {}", address, e).into());
+                }
+            }
+        }
+    };
+
+    // A pipe opened as a file has no read timeout, wait for the reply on a thread. The thread
+    // stays blocked if the service never replies
+    #[cfg(windows)]
+    let line = {
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(exchange(&stream, &payload));
+        });
+        rx.recv_timeout(REPLY_TIMEOUT).map_err(|_| {
+            format!("Service {} did not reply within {} seconds", name, REPLY_TIMEOUT.as_secs())
+        })??
+    };
+
+    #[cfg(unix)]
+    let line = {
+        let stream = std::os::unix::net::UnixStream::connect(&address)
+            .map_err(|e| format!("Service endpoint {} is not available: {}", address, e))?;
+        stream.set_read_timeout(Some(REPLY_TIMEOUT))?;
+        exchange(&stream, &payload)?
+    };
+
+    if line.trim().is_empty() {
+        return Err(format!("Service {} closed the connection without replying", name).into());
+    }
+
+    Ok(serde_json::from_str(line.trim())?)
+}
+
+// Write one request line and read the reply line
+fn exchange<S>(stream: &S, payload: &str) -> std::io::Result<String>
+where
+    for<'a> &'a S: Read + Write,
+{
+    let mut writer = stream;
+    writer.write_all(payload.as_bytes())?;
+    writer.flush()?;
+
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    Ok(line)
+}
+
+/// Serve requests on an endpoint until `running` is cleared.
+/// Blocks the calling thread; `handler` runs on a blocking thread per request.
+pub fn serve<F>(
+    name: &str,
+    running: Arc<AtomicBool>,
+    handler: F,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    F: Fn(Request) -> Response + Send + Sync + 'static,
+{
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(serve_async(endpoint_address(name), running, Arc::new(handler)))
+}
+
+async fn handle_connection<S, F>(stream: S, handler: Arc<F>) -> std::io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    F: Fn(Request) -> Response + Send + Sync + 'static,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut reader = tokio::io::BufReader::new(reader);
+    let mut line = String::new();
+    reader.read_line(&mut line).await?;
+
+    let response = match serde_json::from_str::<Request>(line.trim()) {
+        Ok(request) => tokio::task::spawn_blocking(move || handler(request))
+            .await
+            .unwrap_or_else(|e| Response::error(format!("Request handler failed: {}", e))),
+        Err(e) => Response::error(format!("Invalid request: {}", e)),
+    };
+
+    let mut payload = serde_json::to_string(&response).unwrap_or_default();
+    payload.push('\n');
+    writer.write_all(payload.as_bytes()).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+#[cfg(unix)]
+async fn serve_async<F>(
+    address: String,
+    running: Arc<AtomicBool>,
+    handler: Arc<F>,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    F: Fn(Request) -> Response + Send + Sync + 'static,
+{
+    // A stale socket from a previous run would make bind fail
+    let _ = std::fs::remove_file(&address);
+    let listener = tokio::net::UnixListener::bind(&address)?;
+
+    while running.load(Ordering::SeqCst) {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (stream, _) = accepted?;
+                let handler = handler.clone();
+                tokio::spawn(async move {
+                    let _ = handle_connection(stream, handler).await;
+                });
+            }
+            _ = tokio::time::sleep(Duration::from_secs(1)) => {}
+        }
+    }
+
+    let _ = std::fs::remove_file(&address);
+    Ok(())
+}
+
+#[cfg(windows)]
+async fn serve_async<F>(
+    address: String,
+    running: Arc<AtomicBool>,
+    handler: Arc<F>,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    F: Fn(Request) -> Response + Send + Sync + 'static,
+{
+    use tokio::net::windows::named_pipe::{NamedPipeServer, ServerOptions};
+    use windows::Win32::Security::Authorization::{
+        ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1,
+    };
+    use windows::Win32::Foundation::{HLOCAL, LocalFree};
+    use windows::Win32::Security::{PSECURITY_DESCRIPTOR, SECURITY_ATTRIBUTES};
+    use windows::core::PCWSTR;
+
+    // Allocated by ConvertStringSecurityDescriptorToSecurityDescriptorW, freed with the pipe
+    struct Descriptor(PSECURITY_DESCRIPTOR);
+    impl Drop for Descriptor {
+        fn drop(&mut self) {
+            unsafe {
+                LocalFree(Some(HLOCAL(self.0.0)));
+            }
+        }
+    }
+
+    // SYSTEM and administrators get full access, interactive users the client access so
+    // `link` and `sync` work without elevation. The daemon validates every name it is
+    // sent before it touches the hosts file
+    let sddl: Vec<u16> = format!("D:(A;;GA;;;SY)(A;;GA;;;BA)(A;;{:#x};;;IU)", PIPE_CLIENT_ACCESS)
+        .encode_utf16()
+        .chain(Some(0))
+        .collect();
+    let mut descriptor = Descriptor(PSECURITY_DESCRIPTOR::default());
+    unsafe {
+        ConvertStringSecurityDescriptorToSecurityDescriptorW(
+            PCWSTR::from_raw(sddl.as_ptr()),
+            SDDL_REVISION_1,
+            &mut descriptor.0,
+            None,
+        )?;
+    }
+    let mut attributes = SECURITY_ATTRIBUTES {
+        nLength: std::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+        lpSecurityDescriptor: descriptor.0.0,
+        ..Default::default()
+    };
+
+    let mut create = |first: bool| -> std::io::Result<NamedPipeServer> {
+        unsafe {
+            ServerOptions::new()
+                .first_pipe_instance(first)
+                .create_with_security_attributes_raw(
+                    &address,
+                    &mut attributes as *mut SECURITY_ATTRIBUTES as *mut std::ffi::c_void,
+                )
+        }
+    };
+
+    let mut server = create(true)?;
+
+    while running.load(Ordering::SeqCst) {
+        tokio::select! {
+            connected = server.connect() => {
+                connected?;
+                let client = std::mem::replace(&mut server, create(false)?);
+                let handler = handler.clone();
+                tokio::spawn(async move {
+                    let _ = handle_connection(client, handler).await;
+                });
+            }
+            _ = tokio::time::sleep(Duration::from_secs(1)) => {}
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,289 @@
+//! Append-only journal of the changes laracli made to the machine, one JSON
+//! object per line, shown by `laracli history`.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::config;
+use crate::helpers::logger::{self, LogConfig};
+
+pub const JOURNAL_PATH: &str = r"C:\laracli\journal.jsonl";
+
+// Rotate once the journal is larger than this, history is never dropped for its age
+const MAX_SIZE: u64 = 5 * 1024 * 1024;
+// A lock older than this was left behind by a process that died while rotating
+const STALE_LOCK: std::time::Duration = std::time::Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EventKind {
+    SiteLinked,
+    SiteUnlinked,
+    HostAdded,
+    HostRemoved,
+    NginxReloaded,
+    ProcessRestarted,
+    ServiceStarted,
+    ServiceStopped,
+    Error,
+}
+
+impl EventKind {
+    pub fn name(&self) -> String {
+        serde_json::to_value(self)
+            .ok()
+            .and_then(|value| value.as_str().map(|name| name.to_string()))
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Event {
+    // RFC 3339 local time
+    pub time: String,
+    pub kind: EventKind,
+    // site, host or process the event is about
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub subject: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub detail: String,
+    // binary that made the change
+    pub source: String,
+}
+
+impl Event {
+    pub fn time(&self) -> Option<DateTime<Local>> {
+        DateTime::parse_from_rfc3339(&self.time)
+            .ok()
+            .map(|time| time.with_timezone(&Local))
+    }
+}
+
+// Rotated journal files kept, `logging.journal_keep` in config.json
+fn journal_keep() -> usize {
+    config::read_config()
+        .map(|config| config.logging.journal_keep)
+        .unwrap_or_else(|_| LogConfig::default().journal_keep)
+}
+
+// Held while the journal rotates, the CLI and the service both append to it
+struct RotationLock(PathBuf);
+
+impl RotationLock {
+    fn acquire(path: &Path) -> Option<RotationLock> {
+        let lock = path.with_extension("lock");
+        let stale = fs::metadata(&lock)
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|modified| modified.elapsed().is_ok_and(|age| age > STALE_LOCK));
+        if stale {
+            let _ = fs::remove_file(&lock);
+        }
+        OpenOptions::new().write(true).create_new(true).open(&lock).ok()?;
+        Some(RotationLock(lock))
+    }
+}
+
+impl Drop for RotationLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+fn too_large(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|metadata| metadata.len() > MAX_SIZE)
+}
+
+// Only one process rotates, the others append to whichever file is current
+fn rotate_if_needed(path: &Path, keep: usize) -> std::io::Result<()> {
+    if !too_large(path) {
+        return Ok(());
+    }
+    let Some(_lock) = RotationLock::acquire(path) else {
+        return Ok(());
+    };
+    // Another process may have rotated it before we got the lock
+    if too_large(path) {
+        logger::rotate(path, keep)?;
+    }
+    Ok(())
+}
+
+/// Append an event, failures to write the journal are ignored
+pub fn record(kind: EventKind, subject: &str, detail: &str) {
+    // Nothing happened during a dry run
+    if crate::helpers::plan::is_dry_run() {
+        return;
+    }
+    let event = Event {
+        time: Local::now().to_rfc3339(),
+        kind,
+        subject: subject.to_string(),
+        detail: detail.to_string(),
+        source: std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+            .unwrap_or_default(),
+    };
+    let _ = append(Path::new(JOURNAL_PATH), &event);
+}
+
+fn append(path: &Path, event: &Event) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    rotate_if_needed(path, journal_keep())?;
+    // One write per line so the CLI and the service can append concurrently
+    let line = format!("{}\n", serde_json::to_string(event)?);
+    OpenOptions::new().create(true).append(true).open(path)?.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Which events `laracli history` shows
+#[derive(Debug, Default, Clone)]
+pub struct Filter {
+    pub kind: Option<EventKind>,
+    // substring of the subject, e.g. a site name
+    pub subject: Option<String>,
+    pub since: Option<DateTime<Local>>,
+    // newest events kept
+    pub limit: Option<usize>,
+}
+
+impl Filter {
+    fn matches(&self, event: &Event) -> bool {
+        self.kind.is_none_or(|kind| kind == event.kind)
+            && self
+                .subject
+                .as_ref()
+                .is_none_or(|subject| event.subject.to_lowercase().contains(&subject.to_lowercase()))
+            && self
+                .since
+                .is_none_or(|since| event.time().is_some_and(|time| time >= since))
+    }
+}
+
+/// Events of the journal and its rotated files, oldest first
+pub fn read(filter: &Filter) -> Vec<Event> {
+    let path = Path::new(JOURNAL_PATH);
+    let mut files: Vec<PathBuf> = (1..)
+        .map(|index| logger::rotated_path(path, index))
+        .take_while(|rotated| rotated.exists())
+        .collect();
+    files.reverse();
+    files.push(path.to_path_buf());
+    let contents = files
+        .iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .collect::<Vec<_>>()
+        .join("\n");
+    parse(&contents, filter)
+}
+
+pub fn parse(contents: &str, filter: &Filter) -> Vec<Event> {
+    let events: Vec<Event> = contents
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Event>(line).ok())
+        .filter(|event| filter.matches(event))
+        .collect();
+    let skip = filter.limit.map_or(0, |limit| events.len().saturating_sub(limit));
+    events.into_iter().skip(skip).collect()
+}
+
+/// `30m`, `12h` or `7d` before now
+pub fn parse_since(value: &str) -> Result<DateTime<Local>, String> {
+    let value = value.trim();
+    let invalid = || format!("Invalid duration: {} (use e.g. 30m, 12h or 7d)", value);
+    let (split, unit) = value.char_indices().last().ok_or_else(invalid)?;
+    let amount: i64 = value[..split].parse().map_err(|_| invalid())?;
+    let duration = match unit {
+        'm' => Duration::minutes(amount),
+        'h' => Duration::hours(amount),
+        'd' => Duration::days(amount),
+        _ => return Err(invalid()),
+    };
+    Ok(Local::now() - duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(time: &str, kind: EventKind, subject: &str) -> String {
+        serde_json::to_string(&Event {
+            time: time.to_string(),
+            kind,
+            subject: subject.to_string(),
+            detail: String::new(),
+            source: "laracli-service".to_string(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_filter_events() {
+        let now = Local::now();
+        let old = (now - Duration::days(3)).to_rfc3339();
+        let recent = now.to_rfc3339();
+        let contents = [
+            event(&old, EventKind::SiteLinked, "blog"),
+            "not json".to_string(),
+            event(&recent, EventKind::HostAdded, "blog"),
+            event(&recent, EventKind::SiteLinked, "shop"),
+        ]
+        .join("\n");
+
+        assert_eq!(parse(&contents, &Filter::default()).len(), 3);
+
+        let linked = Filter {
+            kind: Some(EventKind::SiteLinked),
+            ..Default::default()
+        };
+        assert_eq!(parse(&contents, &linked).len(), 2);
+
+        let recent_blog = Filter {
+            subject: Some("BLOG".to_string()),
+            since: Some(parse_since("1d").unwrap()),
+            ..Default::default()
+        };
+        let events = parse(&contents, &recent_blog);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, EventKind::HostAdded);
+
+        let last = Filter {
+            limit: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(parse(&contents, &last)[0].subject, "shop");
+        assert!(parse_since("5w").is_err());
+        assert_eq!(EventKind::NginxReloaded.name(), "nginx-reloaded");
+    }
+
+    #[test]
+    fn test_rotation_waits_for_the_lock() {
+        let dir = std::env::temp_dir().join(format!("laracli-journal-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("journal.jsonl");
+        let large = "x".repeat(MAX_SIZE as usize + 1);
+
+        // Another process is rotating, leave the file alone
+        fs::write(&path, &large).unwrap();
+        fs::write(dir.join("journal.lock"), "").unwrap();
+        rotate_if_needed(&path, 2).unwrap();
+        assert!(path.exists());
+
+        fs::remove_file(dir.join("journal.lock")).unwrap();
+        rotate_if_needed(&path, 2).unwrap();
+        fs::write(&path, &large).unwrap();
+        rotate_if_needed(&path, 2).unwrap();
+        assert!(!path.exists());
+        assert!(dir.join("journal.1.jsonl").exists());
+        assert!(dir.join("journal.2.jsonl").exists());
+        assert!(!dir.join("journal.lock").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,224 @@
+//! Linking and unlinking project folders as transactions, shared by
+//! `laracli link`/`unlink` and the library API. They differ in how they reach
+//! the daemon, which the caller passes as `notify`.
+
+use std::error::Error;
+use std::path::Path;
+
+use crate::helpers::config;
+use crate::helpers::hosts;
+use crate::helpers::ipc;
+use crate::helpers::journal::{self, EventKind};
+use crate::helpers::nginx;
+use crate::helpers::project::ProjectType;
+use crate::helpers::transaction::{Transaction, restore_file};
+use crate::error::LaracliError;
+use crate::helpers::plan;
+
+// Longest a link or unlink may hold the daemon, in case the CLI dies before resuming it
+const PAUSE_SECONDS: u64 = 60;
+
+// Holds the daemon's config reloads and reconciles until dropped, so they do not act on a
+// config.json that a failing transaction is about to roll back
+struct DaemonPause;
+
+impl DaemonPause {
+    fn start() -> DaemonPause {
+        if !plan::is_dry_run() {
+            // Nothing to hold when the service is not running
+            let _ = ipc::send(ipc::DAEMON_ENDPOINT, &ipc::Request::Pause { seconds: PAUSE_SECONDS });
+        }
+        DaemonPause
+    }
+}
+
+impl Drop for DaemonPause {
+    fn drop(&mut self) {
+        if !plan::is_dry_run() {
+            let _ = ipc::send(ipc::DAEMON_ENDPOINT, &ipc::Request::Resume);
+        }
+    }
+}
+
+/// Settings of a linked site, the same as the `laracli link` flags
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkOptions {
+    /// Serve as this type instead of the detected one
+    pub kind: Option<ProjectType>,
+    pub wildcard: bool,
+    pub spa: bool,
+}
+
+/// Link `path`: config.json, its Nginx config, then `notify` has the host
+/// entry added and Nginx reloaded. Returns the host name without .test.
+pub fn link_site(
+    path: &str,
+    options: &LinkOptions,
+    notify: impl FnOnce(ipc::Request) -> Result<(), Box<dyn Error>>,
+) -> Result<String, LaracliError> {
+    let name = folder_name(path)?;
+    hosts::validate_host_name(&name)?;
+    let nginx_config = nginx::site_config_path(path)?;
+    // Only a host entry this link added is removed again, unknown counts as present
+    let had_host = hosts::laracli_host_names()
+        .map(|names| names.contains(&name))
+        .unwrap_or(true);
+
+    let _pause = DaemonPause::start();
+    let mut transaction = Transaction::new();
+    transaction.step(
+        format!("add {} to linked_paths in config.json", path),
+        || {
+            config::add_to_linked_paths(path)?;
+            config::update_site(path, |site| {
+                site.wildcard = options.wildcard;
+                site.kind = options.kind;
+                site.spa = options.spa;
+            })?;
+            Ok(())
+        },
+        restore_file(&config::get_config_path()),
+    )?;
+    transaction.step(
+        format!("write {}", nginx_config.display()),
+        || Ok(nginx::create_nginx_config(path, None)?),
+        restore_file(&nginx_config),
+    )?;
+    let host = name.clone();
+    transaction.step(
+        format!("add the host entry for {}.test and reload Nginx", name),
+        || {
+            notify(ipc::Request::AddSite {
+                path: Some(path.to_string()),
+                name: name.clone(),
+            })
+        },
+        move || {
+            if had_host {
+                return Ok(());
+            }
+            hosts::update_host_entries(&[], &[host])
+        },
+    )?;
+
+    journal::record(EventKind::SiteLinked, &name, path);
+    Ok(name)
+}
+
+/// Proxy `name`.test to `target`, both already checked: config.json, the proxy's Nginx
+/// config, then `notify` has the host entry added and Nginx reloaded
+pub fn proxy_site(
+    name: &str,
+    target: &str,
+    notify: impl FnOnce(ipc::Request) -> Result<(), Box<dyn Error>>,
+) -> Result<(), LaracliError> {
+    let nginx_config = nginx::sites_enabled_dir()?.join(format!("{}.conf", name));
+    let had_host = hosts::laracli_host_names()
+        .map(|names| names.contains(name))
+        .unwrap_or(true);
+
+    let _pause = DaemonPause::start();
+    let mut transaction = Transaction::new();
+    transaction.step(
+        format!("add proxy {} to config.json", name),
+        || Ok(config::add_proxy(name, target)?),
+        restore_file(&config::get_config_path()),
+    )?;
+    transaction.step(
+        format!("write {}", nginx_config.display()),
+        || nginx::create_proxy_config(name, target),
+        restore_file(&nginx_config),
+    )?;
+    let host = name.to_string();
+    transaction.step(
+        format!("add the host entry for {}.test and reload Nginx", name),
+        || {
+            notify(ipc::Request::AddSite {
+                path: None,
+                name: name.to_string(),
+            })
+        },
+        move || {
+            if had_host {
+                return Ok(());
+            }
+            hosts::update_host_entries(&[], &[host])
+        },
+    )?;
+
+    journal::record(EventKind::SiteLinked, name, &format!("proxy to {}", target));
+    Ok(())
+}
+
+/// Unlink the linked folder `path`: config.json, its Nginx config, then
+/// `notify` has the host entries removed and Nginx reloaded. Returns the host
+/// name it was served as.
+pub fn unlink_site(
+    path: &str,
+    notify: impl FnOnce(ipc::Request) -> Result<(), Box<dyn Error>>,
+) -> Result<String, LaracliError> {
+    let config = config::read_config()?;
+    let normalized_path = config::normalize_path_string(path);
+    if !config.linked_paths.contains(&normalized_path) {
+        return Err(LaracliError::NotFound(format!("{} is not a linked path", normalized_path)));
+    }
+    let name = config.site_name(&normalized_path).map_or_else(|| folder_name(path), Ok)?;
+    let nginx_config = nginx::site_config_path(path)?;
+    let subdomains = config
+        .sites
+        .get(&normalized_path)
+        .map(|site| site.subdomains.clone())
+        .unwrap_or_default();
+    // The site and its registered subdomains that are in the hosts file now, put back on undo
+    let present = hosts::laracli_host_names().unwrap_or_default();
+    let host_names: Vec<String> = hosts::site_host_names(&name, &subdomains)
+        .into_iter()
+        .filter(|host| present.contains(host))
+        .collect();
+
+    let _pause = DaemonPause::start();
+    let mut transaction = Transaction::new();
+    transaction.step(
+        format!("remove {} from linked_paths in config.json", path),
+        || Ok(config::remove_from_linked_paths(path)?),
+        restore_file(&config::get_config_path()),
+    )?;
+    transaction.step(
+        format!("delete {}", nginx_config.display()),
+        || {
+            if !plan::exists(&nginx_config) {
+                return Ok(());
+            }
+            nginx::delete_nginx_config(path)
+        },
+        restore_file(&nginx_config),
+    )?;
+    transaction.step(
+        format!("remove the host entries of {}.test and reload Nginx", name),
+        || {
+            notify(ipc::Request::RemoveSite {
+                path: Some(path.to_string()),
+                name: name.clone(),
+                subdomains,
+            })
+        },
+        move || hosts::update_host_entries(&host_names, &[]),
+    )?;
+
+    journal::record(EventKind::SiteUnlinked, &name, path);
+    Ok(name)
+}
+
+// Folder name a path is served under, a drive root like `C:\` has none
+fn folder_name(path: &str) -> Result<String, LaracliError> {
+    Path::new(path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| name.to_string())
+        .ok_or_else(|| {
+            LaracliError::InvalidArgument(format!(
+                "{} is not a project folder, link a folder like C:\\www\\blog",
+                path
+            ))
+        })
+}
@@ -0,0 +1,291 @@
+//! Log files written by the laracli binaries: one line per message with a
+//! level, rotated when they grow too large or too old. Settings come from
+//! the `logging` entry of config.json.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Local, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::journal::{self, EventKind};
+use crate::helpers::logs;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl Level {
+    fn label(&self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    // one JSON object per line, for log shippers and jq
+    Json,
+}
+
+/// `logging` entry of config.json
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogConfig {
+    // messages below this level are dropped
+    #[serde(default = "default_level")]
+    pub level: Level,
+    #[serde(default)]
+    pub format: LogFormat,
+    // rotate once the file is larger than this
+    #[serde(default = "default_max_size_mb")]
+    pub max_size_mb: u64,
+    // rotate once the first line is this many days old
+    #[serde(default = "default_max_age_days")]
+    pub max_age_days: u64,
+    // rotated files kept next to the log, laracli.1.log being the newest
+    #[serde(default = "default_keep")]
+    pub keep: usize,
+    // rotated journal files kept, the journal only rotates by size
+    #[serde(default = "default_journal_keep")]
+    pub journal_keep: usize,
+}
+
+fn default_level() -> Level {
+    Level::Info
+}
+
+fn default_max_size_mb() -> u64 {
+    10
+}
+
+fn default_max_age_days() -> u64 {
+    7
+}
+
+fn default_journal_keep() -> usize {
+    1
+}
+
+fn default_keep() -> usize {
+    5
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        LogConfig {
+            level: default_level(),
+            format: LogFormat::default(),
+            max_size_mb: default_max_size_mb(),
+            max_age_days: default_max_age_days(),
+            keep: default_keep(),
+            journal_keep: default_journal_keep(),
+        }
+    }
+}
+
+impl LogConfig {
+    pub fn is_default(&self) -> bool {
+        *self == LogConfig::default()
+    }
+}
+
+struct Logger {
+    path: PathBuf,
+    config: LogConfig,
+    console: bool,
+}
+
+static LOGGER: Mutex<Option<Logger>> = Mutex::new(None);
+
+fn with_logger<T>(f: impl FnOnce(&mut Logger) -> T) -> T {
+    let mut logger = LOGGER.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let logger = logger.get_or_insert_with(|| Logger {
+        path: PathBuf::from(logs::SERVICE_LOG),
+        config: LogConfig::default(),
+        console: false,
+    });
+    f(logger)
+}
+
+/// Log to `path` from now on, the service log is used until this is called
+pub fn init(path: impl Into<PathBuf>, config: &LogConfig) {
+    let path = path.into();
+    with_logger(|logger| {
+        logger.path = path;
+        logger.config = config.clone();
+    });
+}
+
+/// Apply changed settings, e.g. after config.json was edited
+pub fn configure(config: &LogConfig) {
+    with_logger(|logger| logger.config = config.clone());
+}
+
+/// Also print messages to the console, used when running in the foreground
+pub fn set_console(console: bool) {
+    with_logger(|logger| logger.console = console);
+}
+
+pub fn log(level: Level, msg: &str) {
+    with_logger(|logger| {
+        if level > logger.config.level {
+            return;
+        }
+        let line = format_line(logger.config.format, level, msg);
+
+        if let Some(dir) = logger.path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        let _ = rotate_if_needed(&logger.path, &logger.config);
+        if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&logger.path) {
+            let _ = writeln!(file, "{}", line);
+        }
+
+        // stderr, so a `--json` result on stdout stays parseable
+        if logger.console {
+            eprintln!("{}", line);
+        }
+    });
+}
+
+fn format_line(format: LogFormat, level: Level, msg: &str) -> String {
+    match format {
+        LogFormat::Text => format!("[{}] {:<5} {}", Local::now().format("%Y-%m-%d %H:%M:%S"), level.label(), msg),
+        LogFormat::Json => serde_json::json!({
+            "time": Local::now().to_rfc3339(),
+            "level": level,
+            "pid": std::process::id(),
+            "message": msg,
+        })
+        .to_string(),
+    }
+}
+
+/// Errors also go to the journal shown by `laracli history`
+pub fn error(msg: &str) {
+    log(Level::Error, msg);
+    journal::record(EventKind::Error, "", msg);
+}
+
+pub fn warn(msg: &str) {
+    log(Level::Warn, msg);
+}
+
+pub fn info(msg: &str) {
+    log(Level::Info, msg);
+}
+
+pub fn debug(msg: &str) {
+    log(Level::Debug, msg);
+}
+
+// Timestamp of the first line, text or JSON. File creation times survive a
+// delete and re-create on Windows, so they cannot tell when the log was started.
+fn started_at(path: &Path) -> Option<NaiveDateTime> {
+    let mut first = String::new();
+    BufReader::new(File::open(path).ok()?).take(4096).read_line(&mut first).ok()?;
+    if first.starts_with('{') {
+        let line: serde_json::Value = serde_json::from_str(&first).ok()?;
+        let time = DateTime::parse_from_rfc3339(line.get("time")?.as_str()?).ok()?;
+        return Some(time.with_timezone(&Local).naive_local());
+    }
+    NaiveDateTime::parse_from_str(first.get(1..20)?, "%Y-%m-%d %H:%M:%S").ok()
+}
+
+/// laracli.log -> laracli.<index>.log
+pub fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    match path.extension() {
+        Some(extension) => path.with_file_name(format!("{}.{}.{}", stem, index, extension.to_string_lossy())),
+        None => path.with_file_name(format!("{}.{}", stem, index)),
+    }
+}
+
+/// Move the log aside when it is too large or too old, returns whether it was rotated
+pub fn rotate_if_needed(path: &Path, config: &LogConfig) -> io::Result<bool> {
+    let Ok(metadata) = fs::metadata(path) else {
+        return Ok(false);
+    };
+    let too_large = metadata.len() > config.max_size_mb.saturating_mul(1024 * 1024);
+    let max_age = chrono::Duration::days(config.max_age_days.min(36500) as i64);
+    let too_old = started_at(path).is_some_and(|started| Local::now().naive_local() - started > max_age);
+    if !too_large && !too_old {
+        return Ok(false);
+    }
+    rotate(path, config.keep)?;
+    Ok(true)
+}
+
+/// Move the log to laracli.1.log, shifting older files and keeping `keep` of them
+pub fn rotate(path: &Path, keep: usize) -> io::Result<()> {
+    if keep == 0 {
+        return fs::remove_file(path);
+    }
+    let _ = fs::remove_file(rotated_path(path, keep));
+    for index in (1..keep).rev() {
+        let from = rotated_path(path, index);
+        if from.exists() {
+            fs::rename(&from, rotated_path(path, index + 1))?;
+        }
+    }
+    fs::rename(path, rotated_path(path, 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation_keeps_configured_count() {
+        let dir = std::env::temp_dir().join(format!("laracli-logger-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("laracli.log");
+        let config = LogConfig {
+            max_size_mb: 0,
+            keep: 2,
+            ..Default::default()
+        };
+
+        for round in 1..=3 {
+            fs::write(&path, format!("round {}\n", round)).unwrap();
+            assert!(rotate_if_needed(&path, &config).unwrap());
+        }
+
+        assert!(!path.exists());
+        assert_eq!(fs::read_to_string(dir.join("laracli.1.log")).unwrap(), "round 3\n");
+        assert_eq!(fs::read_to_string(dir.join("laracli.2.log")).unwrap(), "round 2\n");
+        assert!(!dir.join("laracli.3.log").exists());
+        assert!(!rotate_if_needed(&path, &config).unwrap());
+
+        let config = LogConfig {
+            max_age_days: 7,
+            keep: 2,
+            ..Default::default()
+        };
+        fs::write(&path, "[2020-01-01 10:00:00] INFO  old\n").unwrap();
+        assert!(rotate_if_needed(&path, &config).unwrap());
+        fs::write(&path, format!("[{}] INFO  new\n", Local::now().format("%Y-%m-%d %H:%M:%S"))).unwrap();
+        assert!(!rotate_if_needed(&path, &config).unwrap());
+        fs::write(&path, format_line(LogFormat::Json, Level::Info, "new") + "\n").unwrap();
+        assert!(!rotate_if_needed(&path, &config).unwrap());
+        fs::write(&path, "{\"time\":\"2020-01-01T10:00:00+00:00\",\"level\":\"info\"}\n").unwrap();
+        assert!(rotate_if_needed(&path, &config).unwrap());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,223 @@
+//! Where the logs of sites, nginx, php-cgi, MySQL and the laracli service
+//! live, and reading them like `tail`.
+
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::helpers::config::Config;
+use crate::helpers::project::ProjectType;
+use crate::helpers::{path, project, sites};
+
+pub const SERVICE_LOG: &str = r"C:\laracli\laracli.log";
+pub const CLI_LOG: &str = r"C:\laracli\laracli-cli.log";
+
+/// What `laracli logs` can show
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogSource {
+    Site(String),
+    Nginx,
+    Php,
+    Mysql,
+    Service,
+    Cli,
+}
+
+impl LogSource {
+    pub fn parse(name: &str) -> LogSource {
+        match name {
+            "nginx" => LogSource::Nginx,
+            "php" => LogSource::Php,
+            "mysql" => LogSource::Mysql,
+            "service" => LogSource::Service,
+            "cli" => LogSource::Cli,
+            site => LogSource::Site(site.trim_end_matches(".test").to_lowercase()),
+        }
+    }
+}
+
+/// nginx access and error log of a site, relative to the nginx directory
+pub fn site_log_names(name: &str) -> (String, String) {
+    (format!("logs/{}.access.log", name), format!("logs/{}.error.log", name))
+}
+
+/// Error log php-cgi is started with
+pub fn php_error_log() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(path::get_php_path()?.join("logs").join("php-error.log"))
+}
+
+/// php-cgi arguments sending PHP errors to `php_error_log`
+pub fn php_log_args() -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let log_file = php_error_log()?;
+    if let Some(dir) = log_file.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    Ok(vec![
+        "-d".to_string(),
+        "log_errors=On".to_string(),
+        "-d".to_string(),
+        format!("error_log={}", log_file.display()),
+    ])
+}
+
+/// Files of a log source, they may not exist yet
+pub fn log_files(source: &LogSource, config: &Config) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let nginx_path = PathBuf::from(path::get_nginx_path()?);
+    Ok(match source {
+        LogSource::Nginx => vec![nginx_path.join("logs/error.log"), nginx_path.join("logs/access.log")],
+        LogSource::Php => vec![php_error_log()?],
+        LogSource::Mysql => vec![mysql_error_log(Path::new(&path::get_mysql_path()?))],
+        LogSource::Service => vec![PathBuf::from(SERVICE_LOG)],
+        LogSource::Cli => vec![PathBuf::from(CLI_LOG)],
+        LogSource::Site(name) => {
+            let project_path = site_path(config, name);
+            if project_path.is_none() && !config.proxies.contains_key(name) {
+                return Err(format!("No site is served as {}.test", name).into());
+            }
+            let (access, error) = site_log_names(name);
+            let mut files = vec![nginx_path.join(error), nginx_path.join(access)];
+            files.extend(project_path.and_then(|path| framework_log(&path)));
+            files
+        }
+    })
+}
+
+// Linked path or watched project served as `name`
+fn site_path(config: &Config, name: &str) -> Option<PathBuf> {
+    config.find_site(name).map(PathBuf::from).or_else(|| {
+        sites::watched_projects(config)
+            .into_iter()
+            .find(|(project, _)| project.eq_ignore_ascii_case(name))
+            .map(|(_, path)| path)
+    })
+}
+
+// storage/logs/laravel.log, or the newest daily laravel-YYYY-MM-DD.log
+fn framework_log(path: &Path) -> Option<PathBuf> {
+    let kind = project::detect(path)?.kind;
+    if !matches!(kind, ProjectType::Laravel | ProjectType::Statamic) {
+        return None;
+    }
+    let logs = path.join("storage").join("logs");
+    newest_file(&logs, |name| name.starts_with("laravel") && name.ends_with(".log"))
+        .or(Some(logs.join("laravel.log")))
+}
+
+// mysqld writes <host>.err in its data directory unless log-error is set
+fn mysql_error_log(mysql_path: &Path) -> PathBuf {
+    let configured = mysql_path.join("mysql-error.log");
+    if configured.exists() {
+        return configured;
+    }
+    newest_file(&mysql_path.join("data"), |name| name.ends_with(".err")).unwrap_or(configured)
+}
+
+fn newest_file(dir: &Path, matches: impl Fn(&str) -> bool) -> Option<PathBuf> {
+    fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .filter(|entry| entry.file_name().to_str().is_some_and(&matches))
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .max()
+        .map(|(_, path)| path)
+}
+
+/// Last `count` lines of a file, read backwards so large logs stay cheap
+pub fn tail_lines(path: &Path, count: usize) -> io::Result<Vec<String>> {
+    const BLOCK: u64 = 8192;
+    let mut file = File::open(path)?;
+    let length = file.metadata()?.len();
+    let mut start = length;
+    let mut buffer = Vec::new();
+
+    // Read blocks from the end until enough line breaks were seen
+    while start > 0 && buffer.iter().filter(|&&byte| byte == b'\n').count() <= count {
+        let block = BLOCK.min(start);
+        start -= block;
+        let mut chunk = vec![0; block as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&buffer);
+        buffer = chunk;
+    }
+
+    let text = String::from_utf8_lossy(&buffer);
+    let lines: Vec<&str> = text.lines().collect();
+    Ok(lines[lines.len().saturating_sub(count)..]
+        .iter()
+        .map(|line| line.to_string())
+        .collect())
+}
+
+/// Follows files like `tail -f`, starting at their current end
+pub struct LogFollower {
+    files: Vec<(PathBuf, u64)>,
+}
+
+impl LogFollower {
+    pub fn new(paths: &[PathBuf]) -> LogFollower {
+        LogFollower {
+            files: paths
+                .iter()
+                .map(|path| (path.clone(), fs::metadata(path).map(|meta| meta.len()).unwrap_or(0)))
+                .collect(),
+        }
+    }
+
+    /// Lines written since the last poll, with the file they came from
+    pub fn poll(&mut self) -> Vec<(PathBuf, String)> {
+        let mut lines = Vec::new();
+        for (path, offset) in &mut self.files {
+            let Ok(mut file) = File::open(&*path) else {
+                continue;
+            };
+            let length = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+            if length < *offset {
+                // Truncated or rotated, start over
+                *offset = 0;
+            }
+            if length == *offset || file.seek(SeekFrom::Start(*offset)).is_err() {
+                continue;
+            }
+            let mut added = Vec::new();
+            if file.read_to_end(&mut added).is_err() {
+                continue;
+            }
+            // Keep a partial last line for the next poll
+            let complete = added.iter().rposition(|&byte| byte == b'\n').map_or(0, |end| end + 1);
+            *offset += complete as u64;
+            lines.extend(
+                String::from_utf8_lossy(&added[..complete])
+                    .lines()
+                    .map(|line| (path.clone(), line.to_string())),
+            );
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_tail_and_follow() {
+        let path = std::env::temp_dir().join(format!("laracli-logs-{}.log", std::process::id()));
+        let contents: String = (1..=3000).map(|line| format!("line {}\n", line)).collect();
+        fs::write(&path, contents).unwrap();
+
+        assert_eq!(tail_lines(&path, 2).unwrap(), vec!["line 2999", "line 3000"]);
+        assert_eq!(tail_lines(&path, 5000).unwrap().len(), 3000);
+
+        let mut follower = LogFollower::new(std::slice::from_ref(&path));
+        assert!(follower.poll().is_empty());
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        write!(file, "line 3001\nline 30").unwrap();
+        assert_eq!(follower.poll(), vec![(path.clone(), "line 3001".to_string())]);
+        writeln!(file, "02").unwrap();
+        assert_eq!(follower.poll(), vec![(path.clone(), "line 3002".to_string())]);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -3,4 +3,21 @@ pub mod config;
 pub mod nginx;
 pub mod hosts;
 pub mod mysql;
-pub mod download;
\ No newline at end of file
+pub mod download;
+pub mod archive;
+pub mod ipc;
+pub mod process;
+pub mod reconcile;
+pub mod sites;
+pub mod project;
+pub mod watch_rules;
+pub mod dns;
+pub mod network;
+pub mod logs;
+pub mod logger;
+pub mod journal;
+pub mod output;
+pub mod php_ini;
+pub mod plan;
+pub mod transaction;
+pub mod linking;
@@ -1,17 +1,23 @@
-use std::io::Write;
+use crate::error::LaracliError;
 use crate::helpers::path;
+use crate::helpers::plan;
 
-pub fn create_my_ini_file() {
+// Connection settings of the MySQL server managed by laracli
+pub const MYSQL_HOST: &str = "127.0.0.1";
+pub const MYSQL_PORT: u16 = 3306;
+pub const MYSQL_USER: &str = "root";
+
+pub fn create_my_ini_file() -> Result<(), Box<dyn std::error::Error>> {
     //create my.ini file
-    let my_ini_dir_path = path::get_mysql_path().unwrap();
+    let my_ini_dir_path = path::get_mysql_path()?;
     let my_ini_path = std::path::Path::new(&my_ini_dir_path).join("my.ini");
+    let contents = generate_ini_file(&my_ini_dir_path);
 
-    let mut file = std::fs::File::create(&my_ini_path).unwrap();
-    file.write_all(generate_ini_file(&my_ini_path.to_str().unwrap(), &my_ini_dir_path).as_bytes()).unwrap();
-
+    plan::write(&my_ini_path, contents).map_err(|e| LaracliError::from_io("write my.ini", e))?;
+    Ok(())
 }
 
-fn generate_ini_file(path: &str, my_ini_dir_path: &str) -> String {
+fn generate_ini_file(my_ini_dir_path: &str) -> String {
     format!(
         r#"
 [mysqld]
@@ -20,7 +26,7 @@ datadir={}/data
 lc-messages-dir={}/share
 
 [client]
-port=3306
+port={}
 socket=mysql.sock
 log-error=./mysql-error.log
 pid-file=./mysql.pid
@@ -28,6 +34,7 @@ lc-messages-dir=./share
         "#,
         my_ini_dir_path.replace("\\", "/"),
         my_ini_dir_path.replace("\\", "/"),
-        my_ini_dir_path.replace("\\", "/")
+        my_ini_dir_path.replace("\\", "/"),
+        MYSQL_PORT
     )
 }
\ No newline at end of file
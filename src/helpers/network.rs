@@ -0,0 +1,136 @@
+//! Where sites are served: hosts entries, the DNS resolver, nginx `listen`
+//! and php-cgi all use the bind address from config.json.
+
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::LaracliError;
+
+pub const PHP_CGI_PORT: u16 = 9000;
+pub const HTTP_PORT: u16 = 80;
+
+/// `network` entry of config.json
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    #[serde(default = "default_bind_address")]
+    pub bind_address: IpAddr,
+    // also add ::1 host entries and listen on IPv6
+    #[serde(default = "default_ipv6")]
+    pub ipv6: bool,
+}
+
+fn default_bind_address() -> IpAddr {
+    IpAddr::V4(Ipv4Addr::LOCALHOST)
+}
+
+fn default_ipv6() -> bool {
+    true
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        NetworkConfig {
+            bind_address: default_bind_address(),
+            ipv6: default_ipv6(),
+        }
+    }
+}
+
+impl NetworkConfig {
+    pub fn is_default(&self) -> bool {
+        *self == NetworkConfig::default()
+    }
+
+    // Loopback binds keep nginx listening on every interface like before
+    fn is_local(&self) -> bool {
+        self.bind_address.is_loopback() || self.bind_address.is_unspecified()
+    }
+
+    /// Address clients connect to, a wildcard bind is reached through loopback
+    pub fn connect_address(&self) -> IpAddr {
+        match self.bind_address {
+            IpAddr::V4(ip) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+            IpAddr::V6(ip) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+            ip => ip,
+        }
+    }
+
+    /// Addresses site names resolve to, in hosts entries and DNS answers
+    pub fn host_addresses(&self) -> Vec<IpAddr> {
+        let mut addresses = vec![self.connect_address()];
+        if self.ipv6 && self.is_local() && !addresses.contains(&IpAddr::V6(Ipv6Addr::LOCALHOST)) {
+            addresses.push(IpAddr::V6(Ipv6Addr::LOCALHOST));
+        }
+        addresses
+    }
+
+    /// nginx `listen` directives for `port`
+    pub fn listen_directives(&self, port: u16) -> Vec<String> {
+        if !self.is_local() {
+            return vec![format!("listen       {};", SocketAddr::new(self.bind_address, port))];
+        }
+        let mut directives = vec![format!("listen       {};", port)];
+        if self.ipv6 {
+            directives.push(format!("listen       [::]:{};", port));
+        }
+        directives
+    }
+
+    /// Address php-cgi binds to and nginx passes PHP requests to
+    pub fn php_cgi_address(&self) -> SocketAddr {
+        SocketAddr::new(self.connect_address(), PHP_CGI_PORT)
+    }
+
+    /// Address nginx listens on for `port`, every IPv4 interface for a local bind
+    pub fn listen_address(&self, port: u16) -> SocketAddr {
+        if self.is_local() {
+            SocketAddr::from((Ipv4Addr::UNSPECIFIED, port))
+        } else {
+            SocketAddr::new(self.bind_address, port)
+        }
+    }
+}
+
+/// `PortInUse` when another program already listens on `address`
+pub fn ensure_port_free(service: &'static str, address: SocketAddr) -> Result<(), LaracliError> {
+    match TcpListener::bind(address) {
+        Err(e) if e.kind() == io::ErrorKind::AddrInUse => Err(LaracliError::PortInUse { service, address }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_and_lan_addresses() {
+        let local = NetworkConfig::default();
+        assert_eq!(
+            local.host_addresses(),
+            vec![IpAddr::V4(Ipv4Addr::LOCALHOST), IpAddr::V6(Ipv6Addr::LOCALHOST)]
+        );
+        assert_eq!(local.listen_directives(80), vec!["listen       80;", "listen       [::]:80;"]);
+        assert_eq!(local.php_cgi_address().to_string(), "127.0.0.1:9000");
+
+        let lan = NetworkConfig {
+            bind_address: "192.168.1.20".parse().unwrap(),
+            ipv6: true,
+        };
+        assert_eq!(lan.host_addresses(), vec!["192.168.1.20".parse::<IpAddr>().unwrap()]);
+        assert_eq!(lan.listen_directives(80), vec!["listen       192.168.1.20:80;"]);
+        assert_eq!(lan.php_cgi_address().to_string(), "192.168.1.20:9000");
+    }
+
+    #[test]
+    fn test_port_in_use() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let error = ensure_port_free("Nginx", address).unwrap_err();
+        assert_eq!(error.exit_code(), 5);
+        drop(listener);
+        assert!(ensure_port_free("Nginx", address).is_ok());
+    }
+}
@@ -1,65 +1,85 @@
-use std::fs::OpenOptions;
-use std::io::Write;
-use crate::helpers::{path};
-
-
-pub fn create_global_nginx_config() -> Result<(), Box<dyn std::error::Error>> {
-    let  nginx_path = path::get_nginx_path().unwrap();
+use std::process::Command;
+use crate::helpers::config::{self, SiteConfig};
+use crate::helpers::journal::{self, EventKind};
+use crate::helpers::network::{self, NetworkConfig};
+use crate::helpers::{hosts, logs, path};
+use crate::helpers::project::{self, ProjectType};
+use crate::error::LaracliError;
+use crate::helpers::plan::{self, Step};
+
+
+pub fn create_global_nginx_config() -> Result<(), LaracliError> {
+    let nginx_path = path::get_nginx_path()?;
     let global_config_path = std::path::Path::new(&nginx_path).join("conf/nginx.conf");
 
-    //check global nginx.conf exists
-        let mut file = OpenOptions::new()
-            .create(true)
-            .truncate(true)
-            .write(true)
-            .open(&global_config_path)?;
-
-    let config_content = generate_nginx_global_config(std::path::Path::new(&nginx_path).join("sites-enabled").to_str().unwrap());
+    let config_content = generate_nginx_global_config(
+        &std::path::Path::new(&nginx_path).join("sites-enabled").to_string_lossy(),
+        &network_config(),
+    );
 
-    file.write_all(config_content.as_bytes())?;
-    return Ok(());
+    plan::write(&global_config_path, config_content)?;
+    Ok(())
 }
 
 
-pub fn create_nginx_config(path: &str, host_name: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
-    let nginx_path = path::get_nginx_path().unwrap();
+// Reached with paths from config.json by the reconciler and the daemon, so nothing here may panic
+pub fn create_nginx_config(path: &str, host_name: Option<&str>) -> Result<(), LaracliError> {
+    let nginx_path = path::get_nginx_path()?;
     let global_config_path = std::path::Path::new(&nginx_path).join("conf/nginx.conf");
+    let sites_enabled = std::path::Path::new(&nginx_path).join("sites-enabled");
     
     //check global nginx.conf exists
-    if !global_config_path.exists() {
-        let mut file = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .open(&global_config_path)?;
-
-        let config_content = generate_nginx_global_config(
-            std::path::Path::new(&nginx_path).join("sites-enabled").to_str().unwrap()
-        );
-        file.write_all(config_content.as_bytes())?;
+    if !plan::exists(&global_config_path) {
+        let config_content = generate_nginx_global_config(&sites_enabled.to_string_lossy(), &network_config());
+        plan::write(&global_config_path, config_content)?;
     }
 
     //check if sites-enabled exists
-    if !std::path::Path::new(&nginx_path).join("sites-enabled").exists() {
-        std::fs::create_dir(std::path::Path::new(&nginx_path).join("sites-enabled"))?;
+    if !sites_enabled.exists() {
+        plan::create_dir_all(&sites_enabled)?;
     }
 
-    let config_name = std::path::Path::new(path).file_name().unwrap().to_str().unwrap();
-
-    let mut file = OpenOptions::new()
-        .create(true)
-        .write(true)
-        .open(std::path::Path::new(&nginx_path).join("sites-enabled").join(format!("{}.conf", config_name)))?;
+    let config_name = std::path::Path::new(path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| LaracliError::InvalidArgument(format!("Invalid project path: {}", path)))?;
 
     // Use host_name if provided, otherwise use config_name
     let server_name = host_name.unwrap_or(config_name);
-    let config_content = generate_nginx_site_config(path, server_name);
-
-    file.write_all(config_content.as_bytes())?;
+    let config = config::read_config().unwrap_or_default();
+    let site = config
+        .sites
+        .get(&config::normalize_path_string(path))
+        .cloned()
+        .unwrap_or_default();
+    let config_content = generate_nginx_site_config(path, server_name, &site, &config.network);
+
+    plan::write(sites_enabled.join(format!("{}.conf", config_name)), config_content)?;
     Ok(())
 }
 
 
-fn generate_nginx_global_config(include_path: &str) -> String {
+fn network_config() -> NetworkConfig {
+    config::read_config().map(|config| config.network).unwrap_or_default()
+}
+
+// `listen` lines indented for a server block
+fn listen_lines(network: &NetworkConfig, indent: &str) -> String {
+    network
+        .listen_directives(network::HTTP_PORT)
+        .iter()
+        .map(|directive| format!("{}{}", indent, directive))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Every site logs to its own files in the nginx logs directory
+fn log_lines(name: &str) -> String {
+    let (access, error) = logs::site_log_names(name);
+    format!("    access_log  {}  main;\n    error_log   {};", access, error)
+}
+
+fn generate_nginx_global_config(include_path: &str, network: &NetworkConfig) -> String {
     format!(
         r#"worker_processes  1;
 
@@ -84,10 +104,12 @@ http {{
 
     #gzip  on;
 
+{}
+
     include "{}/*.conf";
 
     server {{
-        listen       80;
+{}
         server_name  localhost;
 
         location / {{
@@ -103,43 +125,91 @@ http {{
 }}
 pid        logs/nginx.pid;
 "#,
-        include_path.replace('\\', "/")
+        CONNECTION_UPGRADE_MAP,
+        include_path.replace('\\', "/"),
+        listen_lines(network, "        ")
     )
 }
 
-pub fn generate_nginx_site_config(path: &str, server_name: &str) -> String {
-    let root_path = {
-        let path = std::path::Path::new(path);
-        if path.join("index.php").exists() || path.join("index.html").exists() || path.join("index.htm").exists() {
-            path.to_string_lossy().into_owned()
-        } else if path.join("public").join("index.php").exists() || path.join("public").join("index.html").exists() || path.join("public").join("index.htm").exists() {
-            path.join("public").to_string_lossy().into_owned()
-        } else {
-            path.to_string_lossy().into_owned()
-        }
-    };
+// First line of every site config laracli writes, configs without it are left alone
+pub const GENERATED_MARKER: &str = "# generated by laracli";
 
-    format!(
-        r#"
-       
+// `server_name` value of a site: the site itself, plus all or the registered subdomains
+pub fn server_names(name: &str, site: &SiteConfig) -> String {
+    let mut names = vec![format!("{}.test", name)];
+    if site.wildcard {
+        names.push(format!("*.{}.test", name));
+    } else {
+        names.extend(site.subdomains.iter().map(|subdomain| format!("{}.{}.test", subdomain, name)));
+    }
+    names.join(" ")
+}
 
-server {{
-    listen       80;
-    server_name  {}.test;
+pub fn generate_nginx_site_config(
+    path: &str,
+    server_name: &str,
+    site: &SiteConfig,
+    network: &NetworkConfig,
+) -> String {
+    // --spa implies a static site
+    let kind = site.kind.or(site.spa.then_some(ProjectType::Static));
+    let (kind, root_path) = match project::resolve(std::path::Path::new(path), kind) {
+        Some(project) => (project.kind, project.document_root.to_string_lossy().into_owned()),
+        None => (ProjectType::Php, path.to_string()),
+    };
+    let root_path = root_path.replace('\\', "/");
 
-    location / {{
+    let locations = match kind {
+        ProjectType::Static => format!(
+            r#"    location / {{
+        root   "{}";
+        index  index.html index.htm;
+        try_files {};
+    }}"#,
+            root_path,
+            // Client-side routes are answered with the app shell
+            if site.spa { "$uri $uri/ /index.html" } else { "$uri $uri/ =404" }
+        ),
+        _ => {
+            // Symfony routes everything through its front controller, the others fall back to it
+            let try_files = match kind {
+                ProjectType::Symfony => "try_files $uri /index.php$is_args$args;",
+                ProjectType::WordPress => "try_files $uri $uri/ /index.php?$args;",
+                _ => "try_files $uri $uri/ /index.php?$query_string;",
+            };
+            format!(
+                r#"    location / {{
         root   "{}";
         index  index.php index.html index.htm;
-        try_files $uri $uri/ /index.php?$query_string;
+        {}
     }}
 
     location ~ \.php$ {{
         root           "{}";
-        fastcgi_pass   127.0.0.1:9000;
+        fastcgi_pass   {};
         fastcgi_index  index.php;
         fastcgi_param  SCRIPT_FILENAME $document_root$fastcgi_script_name;
         include        fastcgi_params;
-    }}
+    }}"#,
+                root_path,
+                try_files,
+                root_path,
+                network.php_cgi_address()
+            )
+        }
+    };
+
+    format!(
+        r#"{}
+# {} site{}
+
+server {{
+{}
+    server_name  {};
+
+{}
+
+{}
 
     location ~ /\.ht {{
         deny all;
@@ -147,14 +217,283 @@ server {{
 }}
 
 "#,
-        server_name,
-        root_path.replace('\\', "/"),
-        root_path.replace('\\', "/")
+        GENERATED_MARKER,
+        kind,
+        if kind == ProjectType::Static && site.spa { " (SPA)" } else { "" },
+        listen_lines(network, "    "),
+        server_names(server_name, site),
+        log_lines(server_name),
+        locations
+    )
+}
+// Sets $connection_upgrade for proxied sites: `upgrade` for WebSocket requests, `close` otherwise
+const CONNECTION_UPGRADE_MAP: &str = "    map $http_upgrade $connection_upgrade {
+        default upgrade;
+        ''      close;
+    }";
+
+// nginx.conf written before proxies existed lacks the map, add it in front of the site includes
+fn ensure_connection_upgrade_map() -> Result<(), Box<dyn std::error::Error>> {
+    let global_config_path = std::path::Path::new(&path::get_nginx_path()?).join("conf/nginx.conf");
+    if !plan::exists(&global_config_path) {
+        return Ok(create_global_nginx_config()?);
+    }
+    let contents = plan::read_to_string(&global_config_path)?;
+    if contents.contains("$connection_upgrade") {
+        return Ok(());
+    }
+    let Some(include) = contents.find("    include \"") else {
+        return Err(format!(
+            "{} has no site include, add this block to its http section:\n{}",
+            global_config_path.display(),
+            CONNECTION_UPGRADE_MAP
+        )
+        .into());
+    };
+    let updated = format!("{}{}\n\n{}", &contents[..include], CONNECTION_UPGRADE_MAP, &contents[include..]);
+    plan::write(&global_config_path, updated)?;
+    Ok(())
+}
+
+// Write sites-enabled/<name>.conf proxying <name>.test to a local server
+pub fn create_proxy_config(name: &str, target: &str) -> Result<(), Box<dyn std::error::Error>> {
+    ensure_connection_upgrade_map()?;
+    let sites_enabled = sites_enabled_dir()?;
+    if !sites_enabled.exists() {
+        plan::create_dir_all(&sites_enabled)?;
+    }
+    let config = config::read_config().unwrap_or_default();
+    let config_content = generate_proxy_site_config(name, target, &config.network);
+    plan::write(sites_enabled.join(format!("{}.conf", name)), config_content)?;
+    Ok(())
+}
+
+pub fn delete_proxy_config(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    plan::remove_file(sites_enabled_dir()?.join(format!("{}.conf", name)))?;
+    Ok(())
+}
+
+/// Accepts `5173`, `127.0.0.1:5173` or a full http(s) URL, returns the URL nginx proxies to
+pub fn normalize_proxy_target(target: &str) -> Result<String, LaracliError> {
+    let target = target.trim().trim_end_matches('/');
+    let url = if !target.is_empty() && target.chars().all(|c| c.is_ascii_digit()) {
+        format!("http://127.0.0.1:{}", target)
+    } else if target.contains("://") {
+        target.to_string()
+    } else {
+        format!("http://{}", target)
+    };
+
+    let rest = url
+        .strip_prefix("http://")
+        .or_else(|| url.strip_prefix("https://"))
+        .ok_or_else(|| {
+            LaracliError::InvalidArgument(format!("Only http and https targets can be proxied: {}", target))
+        })?;
+    if !is_proxy_address(rest) {
+        return Err(LaracliError::InvalidArgument(format!(
+            "Invalid proxy target: {}, expected host[:port][/path]",
+            target
+        )));
+    }
+    Ok(url)
+}
+
+// host[:port][/path] after the scheme. Whitespace, `;`, `{`, `}` or `$` would end or change
+// the proxy_pass directive, so only the characters of plain addresses are allowed
+fn is_proxy_address(address: &str) -> bool {
+    let (authority, path) = address.split_once('/').unwrap_or((address, ""));
+    let (host, port) = if let Some(rest) = authority.strip_prefix('[') {
+        // IPv6, like [::1]:5173
+        let Some((ip, port)) = rest.split_once(']') else {
+            return false;
+        };
+        if ip.is_empty() || !ip.chars().all(|c| c.is_ascii_hexdigit() || c == ':') {
+            return false;
+        }
+        if !port.is_empty() && !port.starts_with(':') {
+            return false;
+        }
+        (None, port.strip_prefix(':'))
+    } else {
+        let (host, port) = match authority.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        };
+        (Some(host), port)
+    };
+
+    host.is_none_or(|host| host.split('.').all(hosts::is_dns_label))
+        && port.is_none_or(|port| port.parse::<u16>().is_ok_and(|port| port > 0))
+        && path
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-._~/%:@+,=&".contains(c))
+}
+
+pub fn generate_proxy_site_config(name: &str, target: &str, network: &NetworkConfig) -> String {
+    format!(
+        r#"{}
+# proxy site
+
+server {{
+{}
+    server_name  {}.test;
+
+{}
+
+    location / {{
+        proxy_pass          {};
+        proxy_http_version  1.1;
+        proxy_set_header    Host $host;
+        proxy_set_header    X-Real-IP $remote_addr;
+        proxy_set_header    X-Forwarded-For $proxy_add_x_forwarded_for;
+        proxy_set_header    X-Forwarded-Host $host;
+        proxy_set_header    X-Forwarded-Proto $scheme;
+        # WebSocket upgrades (Vite HMR, Reverb)
+        proxy_set_header    Upgrade $http_upgrade;
+        proxy_set_header    Connection $connection_upgrade;
+        proxy_read_timeout  1h;
+        proxy_buffering     off;
+    }}
+}}
+
+"#,
+        GENERATED_MARKER,
+        listen_lines(network, "    "),
+        name,
+        log_lines(name),
+        target
     )
 }
+
 pub fn delete_nginx_config(path: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let config_name = std::path::Path::new(&path).file_name().unwrap().to_str().unwrap();
-    let nginx_path = path::get_nginx_path().unwrap();
-    std::fs::remove_file(std::path::Path::new(&nginx_path).join("sites-enabled").join(format!("{}.conf", config_name)))?; 
+    plan::remove_file(site_config_path(path)?)?; 
     Ok(())
-}
\ No newline at end of file
+}
+
+// Location of the sites-enabled config generated for a project directory
+pub fn site_config_path(path: &str) -> Result<std::path::PathBuf, Box<dyn std::error::Error>> {
+    let config_name = std::path::Path::new(&path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| format!("Invalid project path: {}", path))?;
+    let nginx_path = path::get_nginx_path()?;
+    Ok(std::path::Path::new(&nginx_path).join("sites-enabled").join(format!("{}.conf", config_name)))
+}
+
+/// Run `nginx -t`, `NginxInvalid` with its output when the configuration is rejected
+pub fn test_config() -> Result<(), Box<dyn std::error::Error>> {
+    let nginx_path = path::get_nginx_path()?;
+    let nginx_exe = path::require_tool("Nginx", std::path::Path::new(&nginx_path).join("nginx.exe"))?;
+    let output = Command::new(&nginx_exe)
+        .current_dir(&nginx_path)
+        .args(["-p", ".", "-c", "conf/nginx.conf", "-t"])
+        .output()?;
+    if !output.status.success() {
+        return Err(LaracliError::NginxInvalid {
+            output: String::from_utf8_lossy(&output.stderr).into_owned(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+// Ask the running nginx master process to reload its configuration
+pub fn reload_nginx() -> Result<(), Box<dyn std::error::Error>> {
+    if plan::dry_run(Step::Service { name: "Nginx".to_string(), action: "Reload".to_string() }) {
+        return Ok(());
+    }
+    // A rejected config would leave nginx on the old one without telling why
+    test_config()?;
+    let nginx_path = path::get_nginx_path()?;
+    let nginx_exe = format!("{}/nginx.exe", &nginx_path);
+    let output = Command::new(&nginx_exe)
+        .current_dir(&nginx_path)
+        .arg("-p")
+        .arg(".")
+        .arg("-c")
+        .arg("conf/nginx.conf")
+        .arg("-s")
+        .arg("reload")
+        .spawn()?
+        .wait()?;
+    if !output.success() {
+        return Err("Failed to reload Nginx service".into());
+    }
+
+    journal::record(EventKind::NginxReloaded, "", "");
+    Ok(())
+}
+
+pub fn sites_enabled_dir() -> Result<std::path::PathBuf, Box<dyn std::error::Error>> {
+    let nginx_path = path::get_nginx_path()?;
+    Ok(std::path::Path::new(&nginx_path).join("sites-enabled"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_server_names() {
+        let mut site = SiteConfig {
+            subdomains: vec!["acme".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(server_names("shop", &site), "shop.test acme.shop.test");
+
+        site.wildcard = true;
+        assert_eq!(server_names("shop", &site), "shop.test *.shop.test");
+        let generated = generate_nginx_site_config("C:/www/shop", "shop", &site, &NetworkConfig::default());
+        assert!(generated.contains("server_name  shop.test *.shop.test;"));
+        assert!(generated.contains("    listen       80;\n    listen       [::]:80;\n"));
+        assert!(generated.contains("fastcgi_pass   127.0.0.1:9000;"));
+        assert!(generated.contains("access_log  logs/shop.access.log  main;"));
+        assert!(generated.contains("error_log   logs/shop.error.log;"));
+    }
+
+    #[test]
+    fn test_static_sites_have_no_php_handler() {
+        let site = SiteConfig {
+            kind: Some(ProjectType::Static),
+            ..Default::default()
+        };
+        let generated = generate_nginx_site_config("C:/www/docs", "docs", &site, &NetworkConfig::default());
+        assert!(generated.contains("try_files $uri $uri/ =404;"));
+        assert!(!generated.contains("fastcgi_pass"));
+
+        let spa = SiteConfig {
+            spa: true,
+            ..Default::default()
+        };
+        let generated = generate_nginx_site_config("C:/www/app", "app", &spa, &NetworkConfig::default());
+        assert!(generated.contains("# static site (SPA)"));
+        assert!(generated.contains("try_files $uri $uri/ /index.html;"));
+        assert!(!generated.contains("index.php"));
+    }
+
+    #[test]
+    fn test_proxy_targets() {
+        assert_eq!(normalize_proxy_target("5173").unwrap(), "http://127.0.0.1:5173");
+        assert_eq!(normalize_proxy_target("localhost:3000/").unwrap(), "http://localhost:3000");
+        assert_eq!(normalize_proxy_target("https://127.0.0.1:8443").unwrap(), "https://127.0.0.1:8443");
+        assert!(normalize_proxy_target("ws://127.0.0.1:8080").is_err());
+        assert!(normalize_proxy_target(":80").is_err());
+        assert_eq!(normalize_proxy_target("[::1]:5173/app").unwrap(), "http://[::1]:5173/app");
+        assert!(normalize_proxy_target("127.0.0.1:5173; return 302 http://evil").is_err());
+        assert!(normalize_proxy_target("127.0.0.1:5173/}\nserver {").is_err());
+        assert!(normalize_proxy_target("localhost:99999").is_err());
+
+        let generated = generate_proxy_site_config("vite", "http://127.0.0.1:5173", &NetworkConfig::default());
+        assert!(generated.contains("server_name  vite.test;"));
+        assert!(generated.starts_with(GENERATED_MARKER));
+        assert!(generated.contains("proxy_pass          http://127.0.0.1:5173;"));
+        assert!(generated.contains("proxy_set_header    Upgrade $http_upgrade;"));
+        assert!(generated.contains("proxy_set_header    Connection $connection_upgrade;"));
+
+        // Proxies rely on the map in the http section of nginx.conf
+        let global = generate_nginx_global_config("C:/laracli/tools/nginx/sites-enabled", &NetworkConfig::default());
+        let map = global.find("map $http_upgrade $connection_upgrade").unwrap();
+        assert!(map < global.find("include \"C:/laracli/tools/nginx/sites-enabled/*.conf\";").unwrap());
+    }
+}
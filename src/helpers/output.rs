@@ -0,0 +1,177 @@
+//! How commands report back: decorated text by default, nothing but errors
+//! with `--quiet`, or a single JSON object on stdout with `--json`.
+//! Commands print through `say!` and add structured fields with `set`.
+
+use std::cell::Cell;
+use std::error::Error;
+use std::fmt::Display;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Text,
+    Quiet,
+    Json,
+}
+
+static MODE: AtomicU8 = AtomicU8::new(0);
+
+thread_local! {
+    // Set by `quietly`, silences this thread whatever the process-wide mode is
+    static QUIET: Cell<bool> = const { Cell::new(false) };
+}
+// Messages and fields collected for the JSON result
+static MESSAGES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+static DATA: Mutex<Option<Map<String, Value>>> = Mutex::new(None);
+
+/// Run `f` without any output on the calling thread, used by the library API
+pub fn quietly<T>(f: impl FnOnce() -> T) -> T {
+    // Put the previous value back when `f` returns or unwinds
+    struct Restore(bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            QUIET.with(|quiet| quiet.set(self.0));
+        }
+    }
+    let _restore = Restore(QUIET.with(|quiet| quiet.replace(true)));
+    f()
+}
+
+pub fn set_mode(mode: Mode) {
+    MODE.store(mode as u8, Ordering::SeqCst);
+    if mode == Mode::Json {
+        // Messages end up in JSON strings, escape codes would only be noise
+        colored::control::set_override(false);
+    }
+}
+
+pub fn mode() -> Mode {
+    if QUIET.with(Cell::get) {
+        return Mode::Quiet;
+    }
+    match MODE.load(Ordering::SeqCst) {
+        1 => Mode::Quiet,
+        2 => Mode::Json,
+        _ => Mode::Text,
+    }
+}
+
+pub fn is_text() -> bool {
+    mode() == Mode::Text
+}
+
+/// Print one line of progress, kept as a message of the result in JSON mode
+pub fn say(line: impl Display) {
+    match mode() {
+        Mode::Text => println!("{}", line),
+        Mode::Quiet => {}
+        Mode::Json => {
+            if let Ok(mut messages) = MESSAGES.lock() {
+                messages.push(line.to_string());
+            }
+        }
+    }
+}
+
+/// `println!` for command output, see `output::say`
+#[macro_export]
+macro_rules! say {
+    () => {
+        $crate::helpers::output::say("")
+    };
+    ($($arg:tt)*) => {
+        $crate::helpers::output::say(format!($($arg)*))
+    };
+}
+
+/// Add a field to the `data` object of the JSON result
+pub fn set(key: &str, value: impl Serialize) {
+    if QUIET.with(Cell::get) {
+        return;
+    }
+    let value = serde_json::to_value(value).unwrap_or(Value::Null);
+    if let Ok(mut data) = DATA.lock() {
+        data.get_or_insert_with(Map::new).insert(key.to_string(), value);
+    }
+}
+
+/// The result object printed by `--json`
+pub fn result(command: &str, error: Option<&(dyn Error + 'static)>) -> Value {
+    let messages = MESSAGES.lock().map(|messages| messages.clone()).unwrap_or_default();
+    let data = DATA
+        .lock()
+        .ok()
+        .and_then(|data| data.clone())
+        .unwrap_or_default();
+
+    let mut result = serde_json::json!({
+        "ok": error.is_none(),
+        "command": command,
+        "data": data,
+        "messages": messages,
+    });
+    if let Some(error) = error {
+        result["error"] = Value::String(error.to_string());
+        result["code"] = Value::from(crate::error::exit_code(error));
+        if let Some(hint) = crate::error::hint(error) {
+            result["hint"] = Value::String(hint);
+        }
+    }
+    result
+}
+
+/// Report how the command ended and return the process exit code
+pub fn finish(command: &str, outcome: Result<(), Box<dyn Error>>) -> i32 {
+    let error = outcome.err();
+    let error = error.as_deref();
+    match mode() {
+        Mode::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&result(command, error)).unwrap_or_default()
+        ),
+        Mode::Text => {
+            if let Some(error) = error {
+                use colored::Colorize;
+                eprintln!("{} {}", "❌".red(), error.to_string().red());
+                if let Some(hint) = crate::error::hint(error) {
+                    eprintln!("   {}", hint.yellow());
+                }
+            }
+        }
+        Mode::Quiet => {
+            if let Some(error) = error {
+                eprintln!("{}", error);
+            }
+        }
+    }
+
+    error.map(crate::error::exit_code).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_result_collects_messages_and_data() {
+        set_mode(Mode::Json);
+        crate::say!("Linking {}", "blog");
+        set("site", "blog.test");
+
+        let error = crate::error::LaracliError::NginxInvalid {
+            output: "unknown directive \"lisen\"".to_string(),
+        };
+        let result = result("link", Some(&error));
+        assert_eq!(result["ok"], false);
+        assert_eq!(result["command"], "link");
+        assert_eq!(result["data"]["site"], "blog.test");
+        assert_eq!(result["messages"][0], "Linking blog");
+        assert_eq!(result["code"], 7);
+        assert!(result["error"].as_str().unwrap().contains("lisen"));
+        set_mode(Mode::Text);
+    }
+}
@@ -1,7 +1,39 @@
-use std::{env, path::PathBuf};
+use std::cell::RefCell;
+use std::{env, path::{Path, PathBuf}};
 
+use crate::error::LaracliError;
+
+thread_local! {
+    // Directory holding tools/ and config.json instead of the executable's, see `with_home`
+    static HOME: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+}
+
+// Puts the previous home back when `with_home` returns or unwinds
+struct RestoreHome(Option<PathBuf>);
+
+impl Drop for RestoreHome {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        HOME.with(|home| *home.borrow_mut() = previous);
+    }
+}
+
+/// Run `f` with `home` as the laracli directory of the calling thread, used by each
+/// call on a `Laracli` handle so several handles can live in one process
+pub fn with_home<T>(home: &Path, f: impl FnOnce() -> T) -> T {
+    let previous = HOME.with(|current| current.replace(Some(home.to_path_buf())));
+    let _restore = RestoreHome(previous);
+    f()
+}
+
+pub fn home() -> Option<PathBuf> {
+    HOME.with(|home| home.borrow().clone())
+}
 
 pub fn get_current_exe_dir() -> Result<String, Box<dyn std::error::Error>> {
+    if let Some(home) = home() {
+        return Ok(home.to_str().ok_or("Failed to convert home directory to string")?.to_string());
+    }
     let current_exe_path = env::current_exe()?;
     let current_exe_dir = current_exe_path.parent().ok_or("Failed to get parent directory of the executable")?;
     let current_exe_dir_str = current_exe_dir.to_str().ok_or("Failed to convert current exe directory to string")?;
@@ -27,6 +59,15 @@ pub fn get_php_path() -> Result< PathBuf, Box<dyn std::error::Error>> {
     Ok(php_path)
 }
 
+/// `file` of an installed tool, `ToolMissing` when setup has not downloaded it
+pub fn require_tool(tool: &'static str, file: PathBuf) -> Result<PathBuf, LaracliError> {
+    if file.exists() {
+        Ok(file)
+    } else {
+        Err(LaracliError::ToolMissing { tool, path: file })
+    }
+}
+
 pub fn get_tools_path() -> Result< PathBuf, Box<dyn std::error::Error>> {
     let current_exe_dir = get_current_exe_dir()?;
     let tools_dir = std::path::Path::new(&current_exe_dir).join("tools");
@@ -0,0 +1,138 @@
+//! Edits of php.ini: enabling and disabling extensions as pure functions
+//! over the file contents, writing the file is left to the caller.
+
+const EXTENSION_DIR_LINE: &str = "extension_dir = \"ext\"";
+const COMMENTED_EXTENSION_DIR_LINE: &str = ";extension_dir = \"ext\"";
+
+/// New contents of php.ini and a note for every change made
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IniEdit {
+    pub contents: String,
+    pub changes: Vec<&'static str>,
+}
+
+impl IniEdit {
+    pub fn changed(&self) -> bool {
+        !self.changes.is_empty()
+    }
+}
+
+/// Uncomment or add `extension=<name>`, pointing extension_dir at ext/ when it is not set
+pub fn enable_extension(contents: &str, extension: &str) -> IniEdit {
+    let extension_line = format!("extension={}", extension);
+    let commented_extension_line = format!(";extension={}", extension);
+    let mut changes = Vec::new();
+
+    let mut contents = contents.to_string();
+    if contents.contains(COMMENTED_EXTENSION_DIR_LINE) {
+        contents = contents.replace(COMMENTED_EXTENSION_DIR_LINE, EXTENSION_DIR_LINE);
+        changes.push("Uncommented extension_dir");
+    } else if !contents.contains(EXTENSION_DIR_LINE) {
+        contents = format!("{}\n{}", contents.trim_end(), EXTENSION_DIR_LINE);
+        changes.push("Added extension_dir");
+    }
+
+    let mut new_contents = String::new();
+    let mut found = false;
+    for line in contents.lines() {
+        let trimmed = line.trim_start();
+        if trimmed == extension_line {
+            new_contents.push_str(line);
+            found = true;
+        } else if trimmed == commented_extension_line {
+            new_contents.push_str(&extension_line);
+            found = true;
+            changes.push("Uncommented extension");
+        } else {
+            new_contents.push_str(line);
+        }
+        new_contents.push('\n');
+    }
+
+    if !found {
+        new_contents = format!("{}\n{}\n", new_contents.trim_end(), extension_line);
+        changes.push("Added extension");
+    }
+
+    IniEdit {
+        contents: new_contents,
+        changes,
+    }
+}
+
+/// Comment out `extension=<name>`
+pub fn disable_extension(contents: &str, extension: &str) -> IniEdit {
+    let extension_line = format!("extension={}", extension);
+    let mut changes = Vec::new();
+
+    let mut new_contents = String::new();
+    for line in contents.lines() {
+        if line.trim() == extension_line {
+            new_contents.push(';');
+            new_contents.push_str(&extension_line);
+            changes.push("Commented out extension");
+        } else {
+            new_contents.push_str(line);
+        }
+        new_contents.push('\n');
+    }
+
+    IniEdit {
+        contents: new_contents,
+        changes,
+    }
+}
+
+/// Extensions listed in php.ini as (name, enabled), commented lines count as disabled
+pub fn extensions(contents: &str) -> Vec<(String, bool)> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let (enabled, rest) = match line.strip_prefix(';') {
+                Some(rest) => (false, rest.trim_start()),
+                None => (true, line),
+            };
+            let name = rest.strip_prefix("extension=")?.trim().trim_matches('"');
+            let name = name.strip_prefix("php_").unwrap_or(name);
+            let name = name.strip_suffix(".dll").unwrap_or(name);
+            (!name.is_empty() && !name.contains(' ')).then(|| (name.to_string(), enabled))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INI: &str = "[PHP]\n;extension_dir = \"ext\"\n;extension=curl\nextension=mbstring\nextension=pdo_mysql\n";
+
+    #[test]
+    fn enables_and_disables_extensions() {
+        let edit = enable_extension(INI, "curl");
+        assert_eq!(edit.changes, vec!["Uncommented extension_dir", "Uncommented extension"]);
+        assert!(edit.contents.contains("\nextension=curl\n"));
+
+        let again = enable_extension(&edit.contents, "curl");
+        assert!(!again.changed());
+
+        let added = enable_extension(&edit.contents, "intl");
+        assert_eq!(added.changes, vec!["Added extension"]);
+        assert!(added.contents.ends_with("extension=intl\n"));
+
+        // pdo must not touch pdo_mysql
+        let disabled = disable_extension(INI, "pdo");
+        assert!(!disabled.changed());
+        let disabled = disable_extension(INI, "mbstring");
+        assert!(disabled.contents.contains(";extension=mbstring\n"));
+
+        assert_eq!(
+            extensions(INI),
+            vec![
+                ("curl".to_string(), false),
+                ("mbstring".to_string(), true),
+                ("pdo_mysql".to_string(), true),
+            ]
+        );
+    }
+}
@@ -0,0 +1,312 @@
+//! Dry runs with `--dry-run`: helpers that change the system check `dry_run`
+//! first, which records the step instead of letting them perform it. Files
+//! written during a dry run are kept in memory so later reads see them, and
+//! the plan shows a single diff per file against what is on disk.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::say;
+use crate::helpers::output;
+
+// Lines of unchanged context around each change of a diff
+const CONTEXT: usize = 3;
+
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+static STEPS: Mutex<Vec<Step>> = Mutex::new(Vec::new());
+// Contents written during the dry run, `None` once deleted
+static FILES: Mutex<BTreeMap<PathBuf, Option<String>>> = Mutex::new(BTreeMap::new());
+
+/// Something a command would have done
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Step {
+    /// A file created or changed, `diff` is filled in by `steps`
+    WriteFile { path: PathBuf, diff: String },
+    DeleteFile { path: PathBuf },
+    RenameFile { from: PathBuf, to: PathBuf },
+    /// `action` is a verb like "Reload", `name` the service
+    Service { name: String, action: String },
+    Download { url: String, path: PathBuf },
+    Extract { archive: PathBuf, into: PathBuf },
+    Run { command: String },
+    Other { description: String },
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::WriteFile { path, .. } => write!(f, "Write {}", path.display()),
+            Step::DeleteFile { path } => write!(f, "Delete {}", path.display()),
+            Step::RenameFile { from, to } => write!(f, "Rename {} to {}", from.display(), to.display()),
+            Step::Service { name, action } => write!(f, "{} {}", action, name),
+            Step::Download { url, path } => write!(f, "Download {} to {}", url, path.display()),
+            Step::Extract { archive, into } => write!(f, "Extract {} into {}", archive.display(), into.display()),
+            Step::Run { command } => write!(f, "Run {}", command),
+            Step::Other { description } => write!(f, "{}", description),
+        }
+    }
+}
+
+/// Record instead of perform from now on
+pub fn enable() {
+    DRY_RUN.store(true, Ordering::SeqCst);
+}
+
+pub fn is_dry_run() -> bool {
+    DRY_RUN.load(Ordering::SeqCst)
+}
+
+/// Record `step` and return true during a dry run, the caller skips it then
+pub fn dry_run(step: Step) -> bool {
+    if !is_dry_run() {
+        return false;
+    }
+    if let Step::Download { path, .. } = &step {
+        // Lets the archive be extracted and removed later in the plan
+        files().insert(path.clone(), Some(String::new()));
+    }
+    push(step);
+    true
+}
+
+/// `fs::write`, kept in memory during a dry run
+pub fn write(path: impl AsRef<Path>, contents: impl AsRef<str>) -> io::Result<()> {
+    let path = path.as_ref();
+    if !is_dry_run() {
+        return fs::write(path, contents.as_ref());
+    }
+    let mut files = files();
+    if !matches!(files.get(path), Some(Some(_))) {
+        push(Step::WriteFile {
+            path: path.to_path_buf(),
+            diff: String::new(),
+        });
+    }
+    files.insert(path.to_path_buf(), Some(contents.as_ref().to_string()));
+    Ok(())
+}
+
+/// `fs::remove_file`, recorded during a dry run
+pub fn remove_file(path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref();
+    if !is_dry_run() {
+        return fs::remove_file(path);
+    }
+    if !exists(path) {
+        return Err(io::Error::new(io::ErrorKind::NotFound, format!("{} does not exist", path.display())));
+    }
+    if path.exists() {
+        files().insert(path.to_path_buf(), None);
+        push(Step::DeleteFile { path: path.to_path_buf() });
+    } else {
+        // Only written earlier in the plan, it never reaches the disk
+        files().remove(path);
+        if let Ok(mut steps) = STEPS.lock() {
+            steps.retain(|step| !matches!(step, Step::WriteFile { path: written, .. } if written == path));
+        }
+    }
+    Ok(())
+}
+
+/// `fs::read_to_string`, seeing the files written during a dry run
+pub fn read_to_string(path: impl AsRef<Path>) -> io::Result<String> {
+    let path = path.as_ref();
+    match pending(path) {
+        Some(contents) => contents
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{} was deleted", path.display()))),
+        None => fs::read_to_string(path),
+    }
+}
+
+/// `Path::exists`, seeing the files written during a dry run
+pub fn exists(path: impl AsRef<Path>) -> bool {
+    let path = path.as_ref();
+    match pending(path) {
+        Some(contents) => contents.is_some(),
+        None => path.exists(),
+    }
+}
+
+/// `fs::create_dir_all`, skipped during a dry run as writing a file implies it
+pub fn create_dir_all(path: impl AsRef<Path>) -> io::Result<()> {
+    if is_dry_run() {
+        return Ok(());
+    }
+    fs::create_dir_all(path)
+}
+
+/// The recorded steps, file writes that change nothing left out
+pub fn steps() -> Vec<Step> {
+    let files = files();
+    let steps = STEPS.lock().map(|steps| steps.clone()).unwrap_or_default();
+    steps
+        .into_iter()
+        .filter_map(|step| match step {
+            Step::WriteFile { path, .. } => {
+                let new = files.get(&path).cloned().flatten()?;
+                let old = fs::read_to_string(&path).unwrap_or_default();
+                if path.exists() && old == new {
+                    return None;
+                }
+                Some(Step::WriteFile {
+                    diff: unified_diff(&old, &new),
+                    path,
+                })
+            }
+            step => Some(step),
+        })
+        .collect()
+}
+
+/// Print the plan of a finished dry run
+pub fn print() {
+    let steps = steps();
+    output::set("plan", &steps);
+    if steps.is_empty() {
+        say!("{}", "✔ Dry run, nothing would change.".green());
+        return;
+    }
+
+    say!("{}", "Dry run, nothing was changed. Planned changes:".yellow());
+    for step in &steps {
+        say!("  - {}", step);
+        if let Step::WriteFile { diff, .. } = step {
+            for line in diff.lines() {
+                let line = match line.chars().next() {
+                    Some('+') => line.green(),
+                    Some('-') => line.red(),
+                    Some('@') => line.cyan(),
+                    _ => line.dimmed(),
+                };
+                say!("      {}", line);
+            }
+        }
+    }
+}
+
+// What a dry run wrote to `path` so far, `Some(None)` when it was deleted
+fn pending(path: &Path) -> Option<Option<String>> {
+    if !is_dry_run() {
+        return None;
+    }
+    files().get(path).cloned()
+}
+
+fn files() -> std::sync::MutexGuard<'static, BTreeMap<PathBuf, Option<String>>> {
+    FILES.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn push(step: Step) {
+    if let Ok(mut steps) = STEPS.lock() {
+        steps.push(step);
+    }
+}
+
+/// Line diff of `old` and `new` in unified format, without file headers
+pub fn unified_diff(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let lines = diff_lines(&old, &new);
+
+    // Ranges of `lines` around the changes, merged when their context overlaps
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for (i, (tag, _)) in lines.iter().enumerate() {
+        if *tag == ' ' {
+            continue;
+        }
+        let start = i.saturating_sub(CONTEXT);
+        let end = (i + CONTEXT + 1).min(lines.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut diff = String::new();
+    for (start, end) in hunks {
+        let before = &lines[..start];
+        let hunk = &lines[start..end];
+        let old_len = hunk.iter().filter(|(tag, _)| *tag != '+').count();
+        let new_len = hunk.iter().filter(|(tag, _)| *tag != '-').count();
+        // Line numbers are 1-based, an empty side names the line before it
+        let old_start = before.iter().filter(|(tag, _)| *tag != '+').count() + usize::from(old_len > 0);
+        let new_start = before.iter().filter(|(tag, _)| *tag != '-').count() + usize::from(new_len > 0);
+        diff.push_str(&format!("@@ -{},{} +{},{} @@\n", old_start, old_len, new_start, new_len));
+        for (tag, line) in hunk {
+            diff.push(*tag);
+            diff.push_str(line);
+            diff.push('\n');
+        }
+    }
+    diff
+}
+
+// Every line tagged ' ', '-' or '+', from the longest common subsequence of the changed middle
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(char, &'a str)> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    // common[i][j]: length of the common subsequence of old_middle[i..] and new_middle[j..]
+    let (n, m) = (old_middle.len(), new_middle.len());
+    let mut common = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            common[i][j] = if old_middle[i] == new_middle[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines: Vec<(char, &str)> = old[..prefix].iter().map(|line| (' ', *line)).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old_middle[i] == new_middle[j] {
+            lines.push((' ', old_middle[i]));
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(('-', old_middle[i]));
+            i += 1;
+        } else {
+            lines.push(('+', new_middle[j]));
+            j += 1;
+        }
+    }
+    lines.extend(old[old.len() - suffix..].iter().map(|line| (' ', *line)));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diffs_changed_lines_with_context() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\n";
+        let new = "a\nb\nc\nd\nE\nf\ng\nh\ni\nj\n";
+        assert_eq!(
+            unified_diff(old, new),
+            "@@ -2,8 +2,9 @@\n b\n c\n d\n-e\n+E\n f\n g\n h\n i\n+j\n"
+        );
+        assert_eq!(unified_diff("", "x\n"), "@@ -0,0 +1,1 @@\n+x\n");
+        assert_eq!(unified_diff("same\n", "same\n"), "");
+    }
+}
@@ -0,0 +1,119 @@
+use std::net::{SocketAddr, TcpStream};
+use std::path::Path;
+use std::process::{Child, Command};
+use std::time::Duration;
+
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+
+use crate::helpers::{config, logs, mysql, network, path};
+
+/// Processes started by `laracli start-dev`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DevProcess {
+    Php,
+    Nginx,
+    Mysql,
+}
+
+impl DevProcess {
+    pub const ALL: [DevProcess; 3] = [DevProcess::Php, DevProcess::Nginx, DevProcess::Mysql];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DevProcess::Php => "php",
+            DevProcess::Nginx => "nginx",
+            DevProcess::Mysql => "mysql",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<DevProcess> {
+        DevProcess::ALL.into_iter().find(|process| process.name() == name)
+    }
+
+    pub fn port(&self) -> u16 {
+        match self {
+            DevProcess::Php => network::PHP_CGI_PORT,
+            DevProcess::Nginx => network::HTTP_PORT,
+            DevProcess::Mysql => mysql::MYSQL_PORT,
+        }
+    }
+
+    // php-cgi and nginx follow the configured bind address, mysql stays on loopback
+    fn address(&self) -> SocketAddr {
+        let network = config::read_config().map(|config| config.network).unwrap_or_default();
+        match self {
+            DevProcess::Mysql => SocketAddr::from(([127, 0, 0, 1], self.port())),
+            _ => SocketAddr::new(network.connect_address(), self.port()),
+        }
+    }
+
+    /// A process counts as running when its port accepts connections
+    pub fn is_running(&self) -> bool {
+        TcpStream::connect_timeout(&self.address(), Duration::from_millis(500)).is_ok()
+    }
+
+    /// Executable name, used to stop every instance
+    pub fn image_name(&self) -> &'static str {
+        match self {
+            DevProcess::Php => "php-cgi.exe",
+            DevProcess::Nginx => "nginx.exe",
+            DevProcess::Mysql => "mysqld.exe",
+        }
+    }
+
+    /// Stop every instance of the process
+    pub fn stop(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let output = Command::new("taskkill")
+            .args(["/F", "/IM", self.image_name()])
+            .output()?;
+        // taskkill exits with 128 when no instance was running
+        if !output.status.success() && output.status.code() != Some(128) {
+            return Err(format!(
+                "Failed to stop {}: {}",
+                self.name(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Start the process detached from the caller
+    pub fn spawn(&self) -> Result<Child, Box<dyn std::error::Error>> {
+        let mut command = match self {
+            DevProcess::Php => {
+                let php_path = path::get_php_path()?;
+                let mut command = Command::new(path::require_tool("PHP", php_path.join("php-cgi.exe"))?);
+                command.arg("-b").arg(self.address().to_string());
+                command.args(logs::php_log_args()?);
+                command
+            }
+            DevProcess::Nginx => {
+                let nginx_path = path::get_nginx_path()?;
+                let mut command = Command::new(path::require_tool("Nginx", Path::new(&nginx_path).join("nginx.exe"))?);
+                command
+                    .current_dir(&nginx_path)
+                    .arg("-p")
+                    .arg(".")
+                    .arg("-c")
+                    .arg("conf/nginx.conf");
+                command
+            }
+            DevProcess::Mysql => {
+                let mysql_path = path::get_mysql_path()?;
+                let ini_file = Path::new(&mysql_path).join("my.ini");
+                let mut command = Command::new(path::require_tool("MySQL", Path::new(&mysql_path).join("bin/mysqld.exe"))?);
+                command
+                    .arg(format!("--defaults-file={}", ini_file.display()))
+                    .arg("--console");
+                command
+            }
+        };
+
+        #[cfg(windows)]
+        command.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+        Ok(command.spawn()?)
+    }
+}
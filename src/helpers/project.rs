@@ -0,0 +1,227 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Kind of project found in a directory, decides the document root and nginx template
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectType {
+    Laravel,
+    Symfony,
+    WordPress,
+    Statamic,
+    Php,
+    Static,
+}
+
+impl fmt::Display for ProjectType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ProjectType::Laravel => "Laravel",
+            ProjectType::Symfony => "Symfony",
+            ProjectType::WordPress => "WordPress",
+            ProjectType::Statamic => "Statamic",
+            ProjectType::Php => "PHP",
+            ProjectType::Static => "static",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Project {
+    pub kind: ProjectType,
+    pub document_root: PathBuf,
+}
+
+impl ProjectType {
+    pub const ALL: [ProjectType; 6] = [
+        ProjectType::Laravel,
+        ProjectType::Symfony,
+        ProjectType::WordPress,
+        ProjectType::Statamic,
+        ProjectType::Php,
+        ProjectType::Static,
+    ];
+
+    pub fn uses_php(&self) -> bool {
+        *self != ProjectType::Static
+    }
+
+    /// Name used in config.json and on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProjectType::Laravel => "laravel",
+            ProjectType::Symfony => "symfony",
+            ProjectType::WordPress => "wordpress",
+            ProjectType::Statamic => "statamic",
+            ProjectType::Php => "php",
+            ProjectType::Static => "static",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ProjectType> {
+        ProjectType::ALL
+            .into_iter()
+            .find(|kind| kind.name().eq_ignore_ascii_case(name))
+    }
+}
+
+// Folders inside a watched path that are never projects
+pub fn is_ignored_name(name: &str) -> bool {
+    name.starts_with('.') || matches!(name, "node_modules" | "vendor" | "$RECYCLE.BIN")
+}
+
+/// Detect the project in `path`, `None` when it does not look like one
+pub fn detect(path: &Path) -> Option<Project> {
+    if !path.is_dir() {
+        return None;
+    }
+
+    let requires = composer_requires(path);
+    let requires = |package: &str| requires.iter().any(|name| name == package);
+    let public = path.join("public");
+
+    let kind = if requires("statamic/cms") {
+        ProjectType::Statamic
+    } else if path.join("artisan").exists() || requires("laravel/framework") {
+        ProjectType::Laravel
+    } else if requires("symfony/framework-bundle")
+        || (path.join("bin").join("console").exists() && public.join("index.php").exists())
+    {
+        ProjectType::Symfony
+    } else if path.join("wp-config.php").exists() || path.join("wp-load.php").exists() {
+        ProjectType::WordPress
+    } else if path.join("index.php").exists() || public.join("index.php").exists() {
+        ProjectType::Php
+    } else if static_root(path).is_some() {
+        ProjectType::Static
+    } else {
+        return None;
+    };
+
+    Some(Project {
+        kind,
+        document_root: document_root(path, kind),
+    })
+}
+
+/// The project in `path` served as `kind` when given, detected otherwise
+pub fn resolve(path: &Path, kind: Option<ProjectType>) -> Option<Project> {
+    match kind {
+        Some(kind) => Some(Project {
+            kind,
+            document_root: document_root(path, kind),
+        }),
+        None => detect(path),
+    }
+}
+
+fn document_root(path: &Path, kind: ProjectType) -> PathBuf {
+    let public = path.join("public");
+    match kind {
+        ProjectType::Laravel | ProjectType::Symfony | ProjectType::Statamic => public,
+        ProjectType::WordPress => path.to_path_buf(),
+        ProjectType::Php => {
+            if path.join("index.php").exists() || !public.join("index.php").exists() {
+                path.to_path_buf()
+            } else {
+                public
+            }
+        }
+        ProjectType::Static => static_root(path).unwrap_or_else(|| path.to_path_buf()),
+    }
+}
+
+// Directory holding index.html, the project root or a usual build output folder
+fn static_root(path: &Path) -> Option<PathBuf> {
+    std::iter::once(path.to_path_buf())
+        .chain(["public", "dist", "build", "_site"].iter().map(|dir| path.join(dir)))
+        .find(|dir| dir.join("index.html").exists() || dir.join("index.htm").exists())
+}
+
+// Package names from the `require` section of composer.json
+fn composer_requires(path: &Path) -> Vec<String> {
+    let Ok(contents) = fs::read_to_string(path.join("composer.json")) else {
+        return Vec::new();
+    };
+    let Ok(composer) = serde_json::from_str::<serde_json::Value>(&contents) else {
+        return Vec::new();
+    };
+    composer
+        .get("require")
+        .and_then(|require| require.as_object())
+        .map(|require| require.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("laracli-project-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        for (file, contents) in files {
+            let file = dir.join(file);
+            fs::create_dir_all(file.parent().unwrap()).unwrap();
+            fs::write(file, contents).unwrap();
+        }
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn detects_project_types_and_document_roots() {
+        let cases = [
+            ("laravel", vec![("artisan", ""), ("public/index.php", "")], ProjectType::Laravel, "public"),
+            (
+                "statamic",
+                vec![("artisan", ""), ("composer.json", r#"{"require":{"statamic/cms":"^5.0"}}"#)],
+                ProjectType::Statamic,
+                "public",
+            ),
+            (
+                "symfony",
+                vec![("composer.json", r#"{"require":{"symfony/framework-bundle":"7.*"}}"#)],
+                ProjectType::Symfony,
+                "public",
+            ),
+            ("wordpress", vec![("wp-config.php", ""), ("index.php", "")], ProjectType::WordPress, ""),
+            ("php", vec![("index.php", "")], ProjectType::Php, ""),
+            ("static", vec![("dist/index.html", "")], ProjectType::Static, "dist"),
+        ];
+
+        for (name, files, kind, root) in cases {
+            let dir = project_dir(name, &files);
+            let project = detect(&dir).unwrap_or_else(|| panic!("{} not detected", name));
+            assert_eq!(project.kind, kind, "{}", name);
+            assert_eq!(project.document_root, dir.join(root), "{}", name);
+            fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+
+    #[test]
+    fn explicit_type_overrides_detection() {
+        let dir = project_dir("explicit", &[("index.php", ""), ("build/index.html", "")]);
+        let project = resolve(&dir, Some(ProjectType::Static)).unwrap();
+        assert_eq!(project.kind, ProjectType::Static);
+        assert_eq!(project.document_root, dir.join("build"));
+        assert_eq!(resolve(&dir, None).unwrap().kind, ProjectType::Php);
+        assert_eq!(ProjectType::from_name("WordPress"), Some(ProjectType::WordPress));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn skips_folders_that_are_not_projects() {
+        let dir = project_dir("empty", &[("notes.txt", "")]);
+        assert_eq!(detect(&dir), None);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(is_ignored_name("node_modules"));
+        assert!(is_ignored_name(".git"));
+        assert!(!is_ignored_name("blog"));
+    }
+}
@@ -0,0 +1,511 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::config::{self, Config};
+use crate::helpers::journal::{self, EventKind};
+use crate::helpers::{hosts, network, nginx, sites};
+
+/// One change needed to bring the machine in line with config.json
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "kebab-case")]
+pub enum Action {
+    /// The linked directory no longer exists, drop it from config.json
+    UnlinkMissing { path: String },
+    /// Write the nginx config of a linked path
+    CreateNginxConfig { path: String, name: String },
+    /// Write the nginx config of a proxied site
+    CreateProxyConfig { name: String, target: String },
+    /// Delete a generated nginx config no linked path uses
+    DeleteNginxConfig { file: PathBuf },
+    AddHost { name: String },
+    RemoveHost { name: String },
+    /// Reload nginx once after the site configs changed
+    ReloadNginx,
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::UnlinkMissing { path } => write!(f, "unlink missing directory {}", path),
+            Action::CreateNginxConfig { path, name } => {
+                write!(f, "create nginx config for {}.test ({})", name, path)
+            }
+            Action::CreateProxyConfig { name, target } => {
+                write!(f, "create nginx proxy config for {}.test ({})", name, target)
+            }
+            Action::DeleteNginxConfig { file } => {
+                write!(f, "delete orphaned nginx config {}", file.display())
+            }
+            Action::AddHost { name } => write!(f, "add host entry {}.test", name),
+            Action::RemoveHost { name } => write!(f, "remove host entry {}.test", name),
+            Action::ReloadNginx => write!(f, "reload nginx"),
+        }
+    }
+}
+
+/// What is actually on disk, read once so planning has no side effects
+#[derive(Debug, Default)]
+pub struct SystemState {
+    /// Site configs in sites-enabled, by file name
+    pub nginx_configs: HashMap<String, PathBuf>,
+    /// File names of the site configs laracli generated
+    pub generated_configs: HashSet<String>,
+    /// Generated configs laracli would write differently today
+    pub outdated_configs: HashSet<String>,
+    /// Host names laracli added to the hosts file
+    pub host_names: HashSet<String>,
+    /// Host names whose entries point to other addresses than the configured ones
+    pub outdated_hosts: HashSet<String>,
+    /// Linked paths whose directory exists
+    pub existing_paths: HashSet<String>,
+    /// Project folders inside the watched paths, as (host name, path)
+    pub watched_projects: Vec<(String, PathBuf)>,
+}
+
+impl SystemState {
+    // Missing, or generated for another bind address
+    fn needs_config(&self, file_name: &str) -> bool {
+        !self.nginx_configs.contains_key(file_name) || self.outdated_configs.contains(file_name)
+    }
+
+    // Missing, or pointing to old addresses
+    fn needs_host(&self, name: &str) -> bool {
+        !self.host_names.contains(name) || self.outdated_hosts.contains(name)
+    }
+}
+
+/// Result of applying one action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Outcome {
+    #[serde(flatten)]
+    pub action: Action,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+pub fn read_state(config: &Config) -> Result<SystemState, Box<dyn std::error::Error>> {
+    let mut state = SystemState {
+        host_names: hosts::laracli_host_names()?,
+        outdated_hosts: hosts::outdated_laracli_host_names()?,
+        ..Default::default()
+    };
+
+    let listen_directives = config.network.listen_directives(network::HTTP_PORT);
+    let sites_enabled = nginx::sites_enabled_dir()?;
+    if sites_enabled.exists() {
+        for entry in fs::read_dir(&sites_enabled)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("conf") {
+                continue;
+            }
+            let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let contents = fs::read_to_string(&path).unwrap_or_default();
+            if contents.trim_start().starts_with(nginx::GENERATED_MARKER) {
+                state.generated_configs.insert(file_name.to_string());
+                // Written for another bind address, or before sites had their own logs
+                if !listen_directives.iter().all(|directive| contents.contains(directive.as_str()))
+                    || !contents.contains("access_log")
+                {
+                    state.outdated_configs.insert(file_name.to_string());
+                }
+            }
+            state.nginx_configs.insert(file_name.to_string(), path);
+        }
+    }
+
+    for path in &config.linked_paths {
+        if Path::new(path).is_dir() {
+            state.existing_paths.insert(path.clone());
+        }
+    }
+
+    state.watched_projects = sites::watched_projects(config);
+
+    Ok(state)
+}
+
+/// Compare config.json with the machine and list the changes needed, in the order they apply
+pub fn plan(config: &Config, state: &SystemState) -> Vec<Action> {
+    let mut actions = BTreeSet::new();
+    let mut wanted_configs = HashSet::new();
+    let mut wanted_hosts = HashSet::new();
+
+    for path in &config.linked_paths {
+        let Some(name) = config.site_name(path) else {
+            continue;
+        };
+        let Some(file_name) = Path::new(path)
+            .file_name()
+            .and_then(|dir| dir.to_str())
+            .map(|dir| format!("{}.conf", dir))
+        else {
+            continue;
+        };
+
+        if !state.existing_paths.contains(path) {
+            actions.insert(Action::UnlinkMissing { path: path.clone() });
+            continue;
+        }
+
+        if state.needs_config(&file_name) {
+            actions.insert(Action::CreateNginxConfig {
+                path: path.clone(),
+                name: name.clone(),
+            });
+        }
+        for host_name in config.host_names(path) {
+            if !config.dns.enabled && state.needs_host(&host_name) {
+                actions.insert(Action::AddHost { name: host_name.clone() });
+            }
+            wanted_hosts.insert(host_name);
+        }
+        wanted_configs.insert(file_name);
+    }
+
+    // Proxied sites, linked paths win on name clashes
+    let mut proxies: Vec<(&String, &String)> = config.proxies.iter().collect();
+    proxies.sort();
+    for (name, target) in proxies {
+        let file_name = format!("{}.conf", name);
+        if wanted_configs.contains(&file_name) {
+            continue;
+        }
+        if state.needs_config(&file_name) {
+            actions.insert(Action::CreateProxyConfig {
+                name: name.clone(),
+                target: target.clone(),
+            });
+        }
+        if !config.dns.enabled && state.needs_host(name) {
+            actions.insert(Action::AddHost { name: name.clone() });
+        }
+        wanted_configs.insert(file_name);
+        wanted_hosts.insert(name.clone());
+    }
+
+    // Implicit links from the watched paths, explicit links win on name clashes
+    for (name, path) in &state.watched_projects {
+        let file_name = format!("{}.conf", name);
+        if wanted_configs.contains(&file_name) {
+            continue;
+        }
+        if state.needs_config(&file_name) {
+            actions.insert(Action::CreateNginxConfig {
+                path: path.to_string_lossy().into_owned(),
+                name: name.clone(),
+            });
+        }
+        if !config.dns.enabled && state.needs_host(name) {
+            actions.insert(Action::AddHost { name: name.clone() });
+        }
+        wanted_configs.insert(file_name);
+        wanted_hosts.insert(name.clone());
+    }
+
+    for file_name in &state.generated_configs {
+        if !wanted_configs.contains(file_name)
+            && let Some(file) = state.nginx_configs.get(file_name)
+        {
+            actions.insert(Action::DeleteNginxConfig { file: file.clone() });
+        }
+    }
+
+    // With the DNS resolver enabled the hosts file is not managed
+    for name in state.host_names.iter().filter(|_| !config.dns.enabled) {
+        if !wanted_hosts.contains(name) {
+            actions.insert(Action::RemoveHost { name: name.clone() });
+        }
+    }
+
+    if actions.iter().any(|action| {
+        matches!(
+            action,
+            Action::CreateNginxConfig { .. }
+                | Action::CreateProxyConfig { .. }
+                | Action::DeleteNginxConfig { .. }
+        )
+    }) {
+        actions.insert(Action::ReloadNginx);
+    }
+
+    actions.into_iter().collect()
+}
+
+/// Read the machine state and plan the changes for the current config.json
+pub fn plan_current() -> Result<Vec<Action>, Box<dyn std::error::Error>> {
+    let config = config::read_config()?;
+    let state = read_state(&config)?;
+    Ok(plan(&config, &state))
+}
+
+/// Plan like `plan_current`, but leave linked directories that are gone linked and return them
+/// separately, so a drive that is not mounted yet keeps its sites until an explicit `laracli sync`
+pub fn plan_current_keeping_missing() -> Result<(Vec<Action>, Vec<String>), Box<dyn std::error::Error>> {
+    let config = config::read_config()?;
+    let mut state = read_state(&config)?;
+    let missing: Vec<String> = config
+        .linked_paths
+        .iter()
+        .filter(|path| !state.existing_paths.contains(*path))
+        .cloned()
+        .collect();
+    state.existing_paths.extend(missing.iter().cloned());
+    Ok((plan(&config, &state), missing))
+}
+
+// Names of the AddHost and RemoveHost actions, applied with one rewrite of the hosts file
+fn host_changes(actions: &[Action]) -> (Vec<String>, Vec<String>) {
+    let mut add = Vec::new();
+    let mut remove = Vec::new();
+    for action in actions {
+        match action {
+            Action::AddHost { name } => add.push(name.clone()),
+            Action::RemoveHost { name } => remove.push(name.clone()),
+            _ => {}
+        }
+    }
+    (add, remove)
+}
+
+/// Apply planned actions, one failing action does not stop the others
+pub fn apply(actions: &[Action]) -> Vec<Outcome> {
+    // Every host action shares the result of the single hosts file rewrite
+    let mut hosts_result: Option<Result<(), String>> = None;
+    actions
+        .iter()
+        .map(|action| {
+            let result: Result<(), Box<dyn std::error::Error>> = match action {
+                Action::UnlinkMissing { path } => config::remove_from_linked_paths(path)
+                    .map(|_| journal::record(EventKind::SiteUnlinked, path, "directory no longer exists"))
+                    .map_err(|e| e.into()),
+                Action::CreateNginxConfig { path, name } => {
+                    nginx::create_nginx_config(path, Some(name)).map_err(|e| e.into())
+                }
+                Action::CreateProxyConfig { name, target } => nginx::create_proxy_config(name, target),
+                Action::DeleteNginxConfig { file } => fs::remove_file(file).map_err(|e| e.into()),
+                Action::AddHost { .. } | Action::RemoveHost { .. } => hosts_result
+                    .get_or_insert_with(|| {
+                        let (add, remove) = host_changes(actions);
+                        hosts::update_host_entries(&add, &remove).map_err(|e| e.to_string())
+                    })
+                    .clone()
+                    .map_err(|e| e.into()),
+                Action::ReloadNginx => nginx::reload_nginx(),
+            };
+            Outcome {
+                action: action.clone(),
+                error: result.err().map(|e| e.to_string()),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::config::SiteConfig;
+
+    fn config(linked: &[&str]) -> Config {
+        Config {
+            linked_paths: linked.iter().map(|path| path.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn plans_missing_config_and_host_for_existing_link() {
+        let config = config(&["C:/www/blog"]);
+        let state = SystemState {
+            existing_paths: HashSet::from(["C:/www/blog".to_string()]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            plan(&config, &state),
+            vec![
+                Action::CreateNginxConfig {
+                    path: "C:/www/blog".into(),
+                    name: "blog".into()
+                },
+                Action::AddHost { name: "blog".into() },
+                Action::ReloadNginx,
+            ]
+        );
+    }
+
+    #[test]
+    fn unlinks_missing_directories_and_removes_their_leftovers() {
+        let config = config(&["C:/www/gone"]);
+        let state = SystemState {
+            nginx_configs: HashMap::from([("gone.conf".to_string(), PathBuf::from("gone.conf"))]),
+            generated_configs: HashSet::from(["gone.conf".to_string()]),
+            host_names: HashSet::from(["gone".to_string()]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            plan(&config, &state),
+            vec![
+                Action::UnlinkMissing { path: "C:/www/gone".into() },
+                Action::DeleteNginxConfig { file: PathBuf::from("gone.conf") },
+                Action::RemoveHost { name: "gone".into() },
+                Action::ReloadNginx,
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_hand_written_configs() {
+        let mut config = config(&["C:/www/shop"]);
+        config.sites.insert(
+            "C:/www/shop".to_string(),
+            SiteConfig {
+                host_name: Some("store".to_string()),
+                ..Default::default()
+            },
+        );
+        let state = SystemState {
+            nginx_configs: HashMap::from([
+                ("shop.conf".to_string(), PathBuf::from("shop.conf")),
+                ("custom.conf".to_string(), PathBuf::from("custom.conf")),
+            ]),
+            generated_configs: HashSet::from(["shop.conf".to_string()]),
+            host_names: HashSet::from(["store".to_string()]),
+            existing_paths: HashSet::from(["C:/www/shop".to_string()]),
+            ..Default::default()
+        };
+
+        assert!(plan(&config, &state).is_empty());
+    }
+
+    #[test]
+    fn adds_hosts_for_registered_subdomains() {
+        let mut config = config(&["C:/www/shop"]);
+        config.sites.insert(
+            "C:/www/shop".to_string(),
+            SiteConfig {
+                subdomains: vec!["acme".to_string(), "globex".to_string()],
+                ..Default::default()
+            },
+        );
+        let state = SystemState {
+            nginx_configs: HashMap::from([("shop.conf".to_string(), PathBuf::from("shop.conf"))]),
+            host_names: HashSet::from(["shop".to_string(), "acme.shop".to_string(), "old.shop".to_string()]),
+            existing_paths: HashSet::from(["C:/www/shop".to_string()]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            plan(&config, &state),
+            vec![
+                Action::AddHost { name: "globex.shop".into() },
+                Action::RemoveHost { name: "old.shop".into() },
+            ]
+        );
+    }
+
+    #[test]
+    fn rewrites_sites_for_a_changed_bind_address() {
+        let config = config(&["C:/www/blog"]);
+        let state = SystemState {
+            nginx_configs: HashMap::from([("blog.conf".to_string(), PathBuf::from("blog.conf"))]),
+            host_names: HashSet::from(["blog".to_string()]),
+            outdated_hosts: HashSet::from(["blog".to_string()]),
+            outdated_configs: HashSet::from(["blog.conf".to_string()]),
+            existing_paths: HashSet::from(["C:/www/blog".to_string()]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            plan(&config, &state),
+            vec![
+                Action::CreateNginxConfig {
+                    path: "C:/www/blog".into(),
+                    name: "blog".into()
+                },
+                Action::AddHost { name: "blog".into() },
+                Action::ReloadNginx,
+            ]
+        );
+    }
+
+    #[test]
+    fn plans_proxied_sites() {
+        let mut config = config(&["C:/www/api"]);
+        config.proxies.insert("vite".to_string(), "http://127.0.0.1:5173".to_string());
+        config.proxies.insert("api".to_string(), "http://127.0.0.1:3000".to_string());
+        let state = SystemState {
+            nginx_configs: HashMap::from([("api.conf".to_string(), PathBuf::from("api.conf"))]),
+            host_names: HashSet::from(["api".to_string()]),
+            existing_paths: HashSet::from(["C:/www/api".to_string()]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            plan(&config, &state),
+            vec![
+                Action::CreateProxyConfig {
+                    name: "vite".into(),
+                    target: "http://127.0.0.1:5173".into()
+                },
+                Action::AddHost { name: "vite".into() },
+                Action::ReloadNginx,
+            ]
+        );
+    }
+
+    #[test]
+    fn removes_only_the_exact_host() {
+        let config = config(&["C:/www/myblog"]);
+        let state = SystemState {
+            nginx_configs: HashMap::from([("myblog.conf".to_string(), PathBuf::from("myblog.conf"))]),
+            host_names: HashSet::from(["blog".to_string(), "myblog".to_string()]),
+            existing_paths: HashSet::from(["C:/www/myblog".to_string()]),
+            ..Default::default()
+        };
+
+        let actions = plan(&config, &state);
+        assert_eq!(actions, vec![Action::RemoveHost { name: "blog".into() }]);
+        let (add, remove) = host_changes(&actions);
+        let contents = "127.0.0.1\tblog.test #added by laracli\n127.0.0.1\tmyblog.test #added by laracli\n127.0.0.1\tapi.blog.test\n";
+        assert_eq!(
+            hosts::apply_host_changes(contents, &["127.0.0.1".parse().unwrap()], &add, &remove),
+            "127.0.0.1\tmyblog.test #added by laracli\n127.0.0.1\tapi.blog.test\n"
+        );
+    }
+
+    #[test]
+    fn links_watched_projects_implicitly() {
+        let config = Config::default();
+        let state = SystemState {
+            host_names: HashSet::from(["parked".to_string()]),
+            watched_projects: vec![
+                ("parked".to_string(), PathBuf::from("C:/www/parked")),
+                ("fresh".to_string(), PathBuf::from("C:/www/fresh")),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            plan(&config, &state),
+            vec![
+                Action::CreateNginxConfig {
+                    path: "C:/www/fresh".into(),
+                    name: "fresh".into()
+                },
+                Action::CreateNginxConfig {
+                    path: "C:/www/parked".into(),
+                    name: "parked".into()
+                },
+                Action::AddHost { name: "fresh".into() },
+                Action::ReloadNginx,
+            ]
+        );
+    }
+}
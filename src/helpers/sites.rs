@@ -0,0 +1,279 @@
+//! Implicit links: project folders inside a watched path get a
+//! site (nginx config and host entry) without being added to `linked_paths`.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::helpers::config::{self, Config};
+use crate::helpers::watch_rules::WatchRules;
+use crate::helpers::journal::{self, EventKind};
+use crate::helpers::{hosts, nginx, project};
+use crate::helpers::plan;
+
+/// Project folders inside the watched paths, as (host name, path).
+/// Folders whose name is taken or no host name are left out, see `watched_projects_checked`
+pub fn watched_projects(config: &Config) -> Vec<(String, PathBuf)> {
+    watched_projects_checked(config).0
+}
+
+/// A project folder left unserved: another site already uses its folder name, e.g.
+/// C:\www\clients\blog next to C:\www\blog with `--depth 2`, or the name is no host name
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SkippedFolder {
+    pub name: String,
+    pub path: PathBuf,
+    /// Why it is skipped, e.g. "blog.test is already served from C:\www\blog"
+    pub reason: String,
+}
+
+/// Project folders inside the watched paths and the folders skipped because their name is taken
+/// or not a valid host name. Linked paths and proxies keep their names, then the shallowest folder wins
+pub fn watched_projects_checked(config: &Config) -> (Vec<(String, PathBuf)>, Vec<SkippedFolder>) {
+    let mut found = Vec::new();
+    for watched in &config.watched_paths {
+        let rules = WatchRules::load(Path::new(watched), &config.watch_options(watched));
+        found.extend(rules.find_projects());
+    }
+    found.sort_by_key(|path| (path.components().count(), path.clone()));
+    found.dedup();
+
+    // Names are compared like host names and Windows folder names, without case
+    let mut taken: HashMap<String, String> = HashMap::new();
+    let mut linked_paths: Vec<&String> = config.linked_paths.iter().collect();
+    linked_paths.sort();
+    for path in linked_paths {
+        let folder = Path::new(path).file_name().and_then(|name| name.to_str());
+        for name in config.site_name(path).into_iter().chain(folder.map(str::to_string)) {
+            taken.entry(name.to_lowercase()).or_insert_with(|| path.clone());
+        }
+    }
+    for (name, target) in &config.proxies {
+        taken.entry(name.to_lowercase()).or_insert_with(|| target.clone());
+    }
+
+    let mut projects = Vec::new();
+    let mut skipped = Vec::new();
+    for path in found {
+        let Some(name) = path.file_name().and_then(|name| name.to_str()).map(str::to_string) else {
+            continue;
+        };
+        // Callers skip explicit links themselves
+        if is_explicitly_linked(config, &path) {
+            projects.push((name, path));
+            continue;
+        }
+        // `My Project` would be two names in the hosts file and in server_name
+        if let Err(e) = hosts::validate_host_name(&name) {
+            skipped.push(SkippedFolder { name, path, reason: e.to_string() });
+            continue;
+        }
+        match taken.get(&name.to_lowercase()) {
+            Some(owner) => skipped.push(SkippedFolder {
+                reason: format!("{}.test is already served from {}", name, owner),
+                name,
+                path,
+            }),
+            None => {
+                taken.insert(name.to_lowercase(), path.to_string_lossy().into_owned());
+                projects.push((name, path));
+            }
+        }
+    }
+    projects.sort();
+    (projects, skipped)
+}
+
+/// Whether a folder in a watched path should be served: not a dotfolder,
+/// `node_modules` and the like, and a recognised project type
+pub fn is_project(path: &Path) -> bool {
+    let ignored = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_none_or(project::is_ignored_name);
+    !ignored && project::detect(path).is_some()
+}
+
+// Explicit links keep their own settings, the watcher leaves them alone
+pub fn is_explicitly_linked(config: &Config, path: &Path) -> bool {
+    let path = config::normalize_path_string(&path.to_string_lossy());
+    config.linked_paths.contains(&path)
+}
+
+/// What `apply_changes` did
+#[derive(Debug, Default)]
+pub struct ChangeReport {
+    pub linked: Vec<String>,
+    pub unlinked: Vec<String>,
+    pub errors: Vec<String>,
+    pub nginx_reloaded: bool,
+}
+
+/// Link the `added` project folders and tear down the `removed` ones in one go:
+/// nginx configs are written, the hosts file is rewritten once and nginx is
+/// reloaded once. The removed folders may already be gone, only their name is used.
+pub fn apply_changes(config: &Config, added: &[PathBuf], removed: &[PathBuf]) -> ChangeReport {
+    let mut report = ChangeReport::default();
+    let mut add_hosts = Vec::new();
+    let mut remove_hosts = Vec::new();
+
+    for path in removed {
+        match remove_site_config(path) {
+            Ok(name) => {
+                journal::record(EventKind::SiteUnlinked, &name, &path.to_string_lossy());
+                remove_hosts.push(name.clone());
+                report.unlinked.push(name);
+            }
+            Err(e) => report.errors.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    for path in added {
+        match write_site_config(config, path) {
+            Ok(name) => {
+                journal::record(EventKind::SiteLinked, &name, &path.to_string_lossy());
+                // A folder renamed back and forth within the batch keeps its entry
+                remove_hosts.retain(|removed| removed != &name);
+                add_hosts.push(name.clone());
+                report.linked.push(name);
+            }
+            Err(e) => report.errors.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    if let Err(e) = hosts::update_host_entries(&add_hosts, &remove_hosts) {
+        report.errors.push(format!("hosts file: {}", e));
+    }
+
+    if !report.linked.is_empty() || !report.unlinked.is_empty() {
+        match nginx::reload_nginx() {
+            Ok(_) => report.nginx_reloaded = true,
+            Err(e) => report.errors.push(format!("Nginx reload: {}", e)),
+        }
+    }
+
+    report
+}
+
+fn site_name_of(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| format!("Invalid project path: {}", path.display()))?
+        .to_string();
+    hosts::validate_host_name(&name)?;
+    Ok(name)
+}
+
+fn write_site_config(config: &Config, path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    let name = site_name_of(path)?;
+    nginx::create_nginx_config(&path.to_string_lossy(), Some(&name))?;
+    if config.update_env {
+        update_env_app_url(path, &name)?;
+    }
+    Ok(name)
+}
+
+fn remove_site_config(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    let name = site_name_of(path)?;
+    let config_file = nginx::site_config_path(&path.to_string_lossy())?;
+    if plan::exists(&config_file) {
+        // Never delete a config the user wrote by hand
+        let contents = plan::read_to_string(&config_file).unwrap_or_default();
+        if contents.trim_start().starts_with(nginx::GENERATED_MARKER) {
+            plan::remove_file(&config_file)?;
+        }
+    }
+    Ok(name)
+}
+
+// Point APP_URL at the .test domain, creating .env from .env.example when needed
+fn update_env_app_url(path: &Path, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let env_path = path.join(".env");
+    let contents = if plan::exists(&env_path) {
+        plan::read_to_string(&env_path)?
+    } else {
+        let example = path.join(".env.example");
+        if !example.exists() {
+            return Ok(());
+        }
+        fs::read_to_string(&example)?
+    };
+    let app_url = format!("APP_URL=http://{}.test", name);
+    let mut found = false;
+    let mut lines: Vec<String> = contents
+        .lines()
+        .map(|line| {
+            if line.starts_with("APP_URL=") {
+                found = true;
+                app_url.clone()
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    if !found {
+        lines.push(app_url);
+    }
+
+    plan::write(&env_path, lines.join("\n") + "\n")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::watch_rules::WatchOptions;
+    use std::collections::HashSet;
+
+    #[test]
+    fn skips_folders_whose_name_is_taken_or_invalid() {
+        let root = std::env::temp_dir().join(format!("laracli-sites-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for project in ["blog", "clients/blog", "clients/shop", "clients/api", "My Project"] {
+            fs::create_dir_all(root.join(project)).unwrap();
+            fs::write(root.join(project).join("index.php"), "").unwrap();
+        }
+
+        let watched = root.to_string_lossy().into_owned();
+        let mut config = Config {
+            watched_paths: HashSet::from([watched.clone()]),
+            linked_paths: HashSet::from(["/work/shop".to_string()]),
+            ..Default::default()
+        };
+        config.watch_options.insert(
+            watched,
+            WatchOptions {
+                depth: 2,
+                ..Default::default()
+            },
+        );
+
+        let (projects, skipped) = watched_projects_checked(&config);
+        assert_eq!(
+            projects,
+            vec![("api".to_string(), root.join("clients/api")), ("blog".to_string(), root.join("blog"))]
+        );
+        assert_eq!(
+            skipped,
+            vec![
+                SkippedFolder {
+                    name: "My Project".to_string(),
+                    path: root.join("My Project"),
+                    reason: hosts::validate_host_name("My Project").unwrap_err().to_string(),
+                },
+                SkippedFolder {
+                    name: "blog".to_string(),
+                    path: root.join("clients/blog"),
+                    reason: format!("blog.test is already served from {}", root.join("blog").display()),
+                },
+                SkippedFolder {
+                    name: "shop".to_string(),
+                    path: root.join("clients/shop"),
+                    reason: "shop.test is already served from /work/shop".to_string(),
+                },
+            ]
+        );
+        let _ = fs::remove_dir_all(&root);
+    }
+}
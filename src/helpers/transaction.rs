@@ -0,0 +1,116 @@
+//! Changes made of reversible steps, like linking a site. Each step registers
+//! its undo before it runs; when a step fails the registered undos run newest
+//! first and the error lists what was undone, so config.json, the Nginx
+//! configs and the hosts file are left as they were.
+
+use std::error::Error;
+use std::path::Path;
+
+use crate::error::LaracliError;
+use crate::helpers::plan;
+
+pub type Undo = Box<dyn FnOnce() -> Result<(), Box<dyn Error>>>;
+
+/// Dropping it keeps the changes made so far
+#[derive(Default)]
+pub struct Transaction {
+    // Started steps and how to revert them, oldest first
+    undos: Vec<(String, Undo)>,
+}
+
+impl Transaction {
+    pub fn new() -> Transaction {
+        Transaction::default()
+    }
+
+    /// Run `action`, described like "write blog.conf". `undo` also runs when
+    /// `action` itself fails, so it has to cope with a change made partway.
+    pub fn step<T>(
+        &mut self,
+        description: impl Into<String>,
+        action: impl FnOnce() -> Result<T, Box<dyn Error>>,
+        undo: impl FnOnce() -> Result<(), Box<dyn Error>> + 'static,
+    ) -> Result<T, LaracliError> {
+        let description = description.into();
+        self.undos.push((description.clone(), Box::new(undo)));
+        action().map_err(|error| self.rollback(description, error))
+    }
+
+    // Undo the started steps newest first, the error names the failed one
+    fn rollback(&mut self, step: String, error: Box<dyn Error>) -> LaracliError {
+        let mut undone = Vec::new();
+        let mut not_undone = Vec::new();
+        // The failed step registered its undo last, it is only reported when that fails too
+        let failed = self.undos.pop();
+        not_undone.extend(failed.and_then(|(description, undo)| {
+            undo().err().map(|e| format!("{} ({})", description, e))
+        }));
+        while let Some((description, undo)) = self.undos.pop() {
+            match undo() {
+                Ok(()) => undone.push(description),
+                Err(e) => not_undone.push(format!("{} ({})", description, e)),
+            }
+        }
+        LaracliError::RolledBack {
+            step,
+            error: Box::new(error.into()),
+            undone,
+            not_undone,
+        }
+    }
+}
+
+/// Undo that puts `path` back as it is now, removing it if it does not exist yet
+pub fn restore_file(path: &Path) -> Undo {
+    let path = path.to_path_buf();
+    // An error when the old contents are unknown, better left alone than removed
+    let before = plan::exists(&path).then(|| plan::read_to_string(&path));
+    Box::new(move || {
+        match before {
+            Some(Ok(contents)) => plan::write(&path, contents)?,
+            Some(Err(e)) => return Err(format!("{} could not be read before: {}", path.display(), e).into()),
+            None if plan::exists(&path) => plan::remove_file(&path)?,
+            None => {}
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn failed_step_restores_earlier_files() {
+        let dir = std::env::temp_dir().join(format!("laracli-transaction-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let changed = dir.join("config.json");
+        let created = dir.join("blog.conf");
+        fs::write(&changed, "old").unwrap();
+
+        let mut transaction = Transaction::new();
+        transaction
+            .step("update config.json", || Ok(fs::write(&changed, "new")?), restore_file(&changed))
+            .unwrap();
+        transaction
+            .step("write blog.conf", || Ok(fs::write(&created, "server {}")?), restore_file(&created))
+            .unwrap();
+        let error = transaction
+            .step("reload Nginx", || Err::<(), _>("nginx.exe not found".into()), || Ok(()))
+            .unwrap_err();
+
+        assert_eq!(fs::read_to_string(&changed).unwrap(), "old");
+        assert!(!created.exists());
+        match &error {
+            LaracliError::RolledBack { step, undone, not_undone, .. } => {
+                assert_eq!(step, "reload Nginx");
+                assert_eq!(undone, &["write blog.conf", "update config.json"]);
+                assert!(not_undone.is_empty());
+            }
+            other => panic!("expected RolledBack, got {:?}", other),
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,226 @@
+//! Which folders of a watched path are projects: a depth limit, include and
+//! exclude globs from config.json, and a `.laracliignore` file in the
+//! watched path with one exclude pattern per line.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::project;
+
+pub const IGNORE_FILE: &str = ".laracliignore";
+
+/// Per watched path settings, stored in config.json under `watch_options`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchOptions {
+    // how deep projects are searched, 1 = direct children only
+    #[serde(default = "default_depth")]
+    pub depth: usize,
+    // only folders matching one of these become projects
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    // folders matching one of these are skipped with everything below them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+}
+
+fn default_depth() -> usize {
+    1
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        WatchOptions {
+            depth: default_depth(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+        }
+    }
+}
+
+/// Options of one watched path combined with its `.laracliignore`
+#[derive(Debug, Clone)]
+pub struct WatchRules {
+    pub root: PathBuf,
+    pub depth: usize,
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl WatchRules {
+    pub fn load(root: &Path, options: &WatchOptions) -> WatchRules {
+        let mut exclude = options.exclude.clone();
+        if let Ok(contents) = fs::read_to_string(root.join(IGNORE_FILE)) {
+            exclude.extend(
+                contents
+                    .lines()
+                    .map(|line| line.trim())
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(|line| line.trim_end_matches('/').to_string()),
+            );
+        }
+
+        WatchRules {
+            root: root.to_path_buf(),
+            depth: options.depth.max(1),
+            include: options.include.clone(),
+            exclude,
+        }
+    }
+
+    pub fn ignore_file(&self) -> PathBuf {
+        self.root.join(IGNORE_FILE)
+    }
+
+    // Path relative to the watched path with `/` separators
+    fn relative(&self, path: &Path) -> Option<String> {
+        let relative = path.strip_prefix(&self.root).ok()?;
+        let parts: Vec<String> = relative
+            .components()
+            .map(|part| part.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        Some(parts.join("/"))
+    }
+
+    /// How many levels below the watched path `path` is, `None` when outside it
+    pub fn level(&self, path: &Path) -> Option<usize> {
+        path.strip_prefix(&self.root)
+            .ok()
+            .map(|relative| relative.components().count())
+    }
+
+    /// Dotfolders, `node_modules` and exclude matches, at any level
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        let Some(relative) = self.relative(path) else {
+            return true;
+        };
+        let name = relative.rsplit('/').next().unwrap_or_default();
+        project::is_ignored_name(name)
+            || self.exclude.iter().any(|pattern| matches_pattern(pattern, &relative))
+    }
+
+    /// Whether `path` may be served as a project, without looking at its files
+    pub fn is_candidate(&self, path: &Path) -> bool {
+        let Some(relative) = self.relative(path) else {
+            return false;
+        };
+        let level = self.level(path).unwrap_or(0);
+        level >= 1
+            && level <= self.depth
+            && !self.is_excluded(path)
+            && (self.include.is_empty()
+                || self.include.iter().any(|pattern| matches_pattern(pattern, &relative)))
+    }
+
+    /// Whether folders below `path` can still hold projects
+    pub fn should_descend(&self, path: &Path) -> bool {
+        let level = self.level(path).unwrap_or(usize::MAX);
+        level < self.depth && (level == 0 || !self.is_excluded(path))
+    }
+
+    /// Walk the watched path down to `depth` and list the project folders,
+    /// projects are not searched for nested projects
+    pub fn find_projects(&self) -> Vec<PathBuf> {
+        let mut projects = Vec::new();
+        self.find_projects_in(&self.root, &mut projects);
+        projects.sort();
+        projects
+    }
+
+    fn find_projects_in(&self, dir: &Path, projects: &mut Vec<PathBuf>) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() || self.is_excluded(&path) {
+                continue;
+            }
+            if self.is_candidate(&path) && project::detect(&path).is_some() {
+                projects.push(path);
+            } else if self.should_descend(&path) {
+                self.find_projects_in(&path, projects);
+            }
+        }
+    }
+}
+
+// A pattern without `/` is matched against the folder name, otherwise against the relative path
+fn matches_pattern(pattern: &str, relative: &str) -> bool {
+    if pattern.contains('/') {
+        glob_match(pattern.trim_start_matches('/'), relative)
+    } else {
+        let name = relative.rsplit('/').next().unwrap_or(relative);
+        glob_match(pattern, name)
+    }
+}
+
+/// Minimal glob: `*` and `?` stay within one path segment, `**` spans segments.
+/// Matching is case-insensitive like Windows paths.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    glob_match_from(&pattern, &text)
+}
+
+fn glob_match_from(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') if pattern.get(1) == Some(&'*') => {
+            let rest = pattern[2..].strip_prefix(&['/']).unwrap_or(&pattern[2..]);
+            (0..=text.len()).any(|i| glob_match_from(rest, &text[i..]))
+        }
+        Some('*') => {
+            let rest = &pattern[1..];
+            for i in 0..=text.len() {
+                if glob_match_from(rest, &text[i..]) {
+                    return true;
+                }
+                if text.get(i) == Some(&'/') {
+                    break;
+                }
+            }
+            false
+        }
+        Some('?') => {
+            !text.is_empty() && text[0] != '/' && glob_match_from(&pattern[1..], &text[1..])
+        }
+        Some(c) => text.first() == Some(c) && glob_match_from(&pattern[1..], &text[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("legacy-*", "Legacy-Shop"));
+        assert!(glob_match("clients/*", "clients/acme"));
+        assert!(!glob_match("clients/*", "clients/acme/api"));
+        assert!(glob_match("**/archive", "clients/acme/archive"));
+        assert!(glob_match("**/archive", "archive"));
+        assert!(glob_match("sit?", "site"));
+        assert!(!glob_match("sit?", "sites"));
+    }
+
+    #[test]
+    fn test_rules_depth_and_patterns() {
+        let options = WatchOptions {
+            depth: 2,
+            include: Vec::new(),
+            exclude: vec!["archive".to_string(), "clients/old-*".to_string()],
+        };
+        let rules = WatchRules::load(Path::new("/www"), &options);
+
+        assert!(rules.is_candidate(Path::new("/www/blog")));
+        assert!(rules.is_candidate(Path::new("/www/clients/acme")));
+        assert!(!rules.is_candidate(Path::new("/www/clients/acme/api")));
+        assert!(!rules.is_candidate(Path::new("/www/archive")));
+        assert!(!rules.is_candidate(Path::new("/www/clients/old-shop")));
+        assert!(!rules.is_candidate(Path::new("/www/node_modules")));
+        assert!(rules.should_descend(Path::new("/www/clients")));
+        assert!(!rules.should_descend(Path::new("/www/clients/acme")));
+    }
+}
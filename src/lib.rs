@@ -1,2 +1,5 @@
 pub mod utils;
+pub mod error;
 pub mod helpers;
+pub mod daemon;
+pub mod api;
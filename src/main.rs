@@ -1,16 +1,27 @@
 use colored::Colorize;
 mod cli;
 mod commands {
+    pub mod dns;
+    pub mod features;
+    pub mod history;
     pub mod link;
+    pub mod logs;
     pub mod mysql;
     pub mod nginx;
     pub mod php;
     pub mod phpmyadmin;
+    pub mod proxy;
     pub mod setup;
+    pub mod status;
+    pub mod subdomain;
+    pub mod supervise;
+    pub mod sync;
+    pub mod uninstall;
     pub mod watch;
 }
-mod helpers;
-mod utils;
+
+use laracli::helpers::{self, output, plan};
+use laracli::say;
 
 const VERSION: &str = "0.4.3-beta";
 const NAME: &str = "laracli";
@@ -18,93 +29,195 @@ const BUILD_DATE: &str = env!("BUILD_DATE");
 const GIT_HASH: &str = env!("GIT_HASH");
 
 fn print_version() {
-    println!("{} v{}", NAME.bright_cyan(), VERSION.bright_green());
-    println!("Build: {} ({})", BUILD_DATE.dimmed(), GIT_HASH.dimmed());
-    println!("Platform: {}", std::env::consts::OS);
+    say!("{} v{}", NAME.bright_cyan(), VERSION.bright_green());
+    say!("Build: {} ({})", BUILD_DATE.dimmed(), GIT_HASH.dimmed());
+    say!("Platform: {}", std::env::consts::OS);
+    output::set(
+        "version",
+        serde_json::json!({
+            "name": NAME,
+            "version": VERSION,
+            "build_date": BUILD_DATE,
+            "git_hash": GIT_HASH,
+            "platform": std::env::consts::OS,
+        }),
+    );
 }
 
 #[tokio::main]
 async fn main() {
     let cli: cli::Cli = argh::from_env();
+    if cli.json {
+        output::set_mode(output::Mode::Json);
+    } else if cli.quiet {
+        output::set_mode(output::Mode::Quiet);
+    }
 
-    match cli.command {
-        cli::Commands::Reload(_) => {
-            commands::nginx::reload().expect("Failed to reload Nginx");
+    // Commands are recorded in their own file, laracli.log belongs to the service
+    let logging = helpers::config::read_config().unwrap_or_default().logging;
+    helpers::logger::init(helpers::logs::CLI_LOG, &logging);
+    helpers::logger::info(&format!(
+        "laracli {}",
+        std::env::args().skip(1).collect::<Vec<_>>().join(" ")
+    ));
+
+    // Name of the subcommand for the JSON result, the first argument that is not a flag
+    let command = std::env::args()
+        .skip(1)
+        .find(|arg| !arg.starts_with('-'))
+        .unwrap_or_default();
+    // Progress messages would describe changes that are not made, the plan replaces them
+    let dry_run = cli.command.dry_run();
+    let text = output::is_text();
+    if dry_run {
+        plan::enable();
+        if text {
+            output::set_mode(output::Mode::Quiet);
+        }
+    }
+    let outcome = run(cli.command).await;
+    if dry_run {
+        if text {
+            output::set_mode(output::Mode::Text);
+        }
+        if outcome.is_ok() {
+            plan::print();
+        }
+    }
+    if let Err(e) = &outcome {
+        helpers::logger::error(&format!("laracli {} failed: {}", command, e));
+    }
+    std::process::exit(output::finish(&command, outcome));
+}
+
+async fn run(command: cli::Commands) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        cli::Commands::Reload(cli::Reload { service: cli::NginxReload {} }) => {
+            commands::nginx::reload()?;
         }
         cli::Commands::Start(start) => match start.service {
             cli::Service::Nginx(_) => {
-                println!("Starting Nginx...");
-                commands::nginx::start().expect("Failed to start Nginx");
+                say!("Starting Nginx...");
+                commands::nginx::start()?;
             }
             cli::Service::Mysql(_) => {
-                println!("Starting MySQL...");
-                commands::mysql::start().expect("Failed to start MySQL");
+                say!("Starting MySQL...");
+                commands::mysql::start()?;
             }
         },
         cli::Commands::Stop(stop) => match stop.service {
             cli::Service::Nginx(_) => {
-                commands::nginx::stop().expect("Failed to stop Nginx");
+                commands::nginx::stop()?;
             }
             cli::Service::Mysql(_) => {
-                println!("Stopping MySQL...");
-                commands::mysql::stop().expect("Failed to stop MySQL");
+                say!("Stopping MySQL...");
+                commands::mysql::stop()?;
             }
         },
         cli::Commands::Watch(watch) => {
-            println!("Watching directory: {}", watch.path);
-            commands::watch::watch_directory(&watch.path).unwrap();
+            say!("Watching directory: {}", watch.path);
+            let options = helpers::watch_rules::WatchOptions {
+                depth: watch.depth.unwrap_or(1),
+                include: watch.include,
+                exclude: watch.exclude,
+            };
+            commands::watch::watch_directory(&watch.path, options, watch.update_env)?;
         }
         cli::Commands::ListWatched(_) => {
-            commands::watch::list_watched_directories()
-                .expect("Failed to list watched directories");
+            commands::watch::list_watched_directories()?;
         }
         cli::Commands::Unwatch(unwatch_cmd) => {
-            commands::watch::unwatch_directory(&unwatch_cmd.path)
-                .expect("Failed to unwatch directory");
+            commands::watch::unwatch_directory(&unwatch_cmd.path)?;
         }
         cli::Commands::Link(link) => {
-            commands::link::link(&link.path).unwrap();
+            commands::link::link(&link.path, link.wildcard, link.kind.as_deref(), link.spa)?;
         }
         cli::Commands::Unlink(unlink) => {
-            commands::link::unlink(&unlink.path).unwrap();
+            commands::link::unlink(&unlink.path)?;
+        }
+        cli::Commands::Proxy(proxy) => {
+            commands::proxy::proxy(&proxy.name, &proxy.target)?;
         }
+        cli::Commands::Subdomain(subdomain) => match subdomain.action {
+            cli::SubdomainAction::Add(add) => {
+                commands::subdomain::add(&add.site, &add.subdomain)?;
+            }
+            cli::SubdomainAction::Remove(remove) => {
+                commands::subdomain::remove(&remove.site, &remove.subdomain)?;
+            }
+        },
         cli::Commands::Setup(_) => {
-            println!("{}", "Setting up services...".yellow());
-            commands::setup::setup_tools()
-                .await
-                .expect("Failed to setup tools");
-            commands::setup::setup_services().expect("Failed to setup services");
-            commands::setup::setup_permissions().expect("Failed to setup permissions");
-            helpers::config::create_config_file();
-            commands::setup::add_exe_to_path().expect("Failed to add exe to path");
-        }
-        cli::Commands::StartDev(_) => {
-            commands::php::start_php_cgi().expect("Failed to start PHP CGI");
-            commands::nginx::start().expect("Failed to start Nginx");
-            commands::mysql::start().expect("Failed to start MySQL");
+            say!("{}", "Setting up services...".yellow());
+            commands::setup::setup_tools().await?;
+            commands::setup::setup_services()?;
+            commands::setup::setup_permissions()?;
+            helpers::config::create_config_file()?;
+            commands::setup::add_exe_to_path()?;
+        }
+        cli::Commands::Uninstall(uninstall) => {
+            commands::uninstall::uninstall(&commands::uninstall::UninstallOptions {
+                keep_tools: uninstall.keep_tools,
+                keep_data: uninstall.keep_data,
+                yes: uninstall.yes,
+            })?;
+        }
+        cli::Commands::StartDev(run) => {
+            commands::php::start_php_cgi()?;
+            commands::nginx::start()?;
+            commands::mysql::start()?;
+            if run.supervise {
+                helpers::config::set_supervised_processes(&["php", "nginx", "mysql"])?;
+                commands::watch::reload_service()?;
+                commands::supervise::supervise().await?;
+            }
+        }
+        cli::Commands::Status(_) => {
+            commands::status::status()?;
+        }
+        cli::Commands::Sync(sync) => {
+            commands::sync::sync(sync.dry_run)?;
+        }
+        cli::Commands::Logs(logs) => {
+            commands::logs::logs(&logs.source, logs.lines, logs.follow)?;
+        }
+        cli::Commands::History(history) => {
+            commands::history::history(
+                history.kind.as_deref(),
+                history.site.as_deref(),
+                history.since.as_deref(),
+                history.limit,
+            )?;
         }
         cli::Commands::Version(_) => {
             print_version();
         }
-        cli::Commands::Enable(enable) => match enable.feature {
-            cli::Feature::PhpMyAdmin(_) => {
-                commands::phpmyadmin::enable_phpmyadmin().await.expect("Failed to enable phpMyAdmin");
-            }
-        },
+        cli::Commands::Enable(enable) => {
+            commands::features::enable(enable.feature.name()).await?;
+        }
+        cli::Commands::Disable(disable) => {
+            commands::features::disable(disable.feature.name())?;
+        }
+        cli::Commands::Features(_) => {
+            commands::features::list_features();
+        }
         cli::Commands::PhpExtension(ext) => match ext.action {
             cli::PhpExtensionAction::Enable(ext_cmd) => {
-                commands::php::enable_php_extension(&ext_cmd.extension)
-                    .expect("Failed to enable PHP extension");
+                commands::php::enable_php_extension(&ext_cmd.extension)?;
             }
             cli::PhpExtensionAction::Disable(ext_cmd) => {
-                commands::php::disable_php_extension(&ext_cmd.extension)
-                    .expect("Failed to disable PHP extension");
+                commands::php::disable_php_extension(&ext_cmd.extension)?;
             }
         },
         cli::Commands::StopDev(_) => {
-            commands::php::stop_php_cgi().expect("Failed to start PHP CGI");
-            commands::nginx::stop().expect("Failed to start Nginx");
-            commands::mysql::stop().expect("Failed to start MySQL");
+            // Stop supervising first so `start-dev --supervise` does not restart them
+            if !helpers::config::load_config()?.supervise.is_empty() {
+                helpers::config::set_supervised_processes(&[])?;
+                commands::watch::reload_service()?;
+            }
+            commands::php::stop_php_cgi()?;
+            commands::nginx::stop()?;
+            commands::mysql::stop()?;
         }
     }
-}
\ No newline at end of file
+    Ok(())
+}
@@ -8,6 +8,7 @@ use windows::Win32::Security::{
 };
 use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
 use windows::Win32::UI::WindowsAndMessaging::SW_SHOW;
+use crate::say;
 
 /// Check if we're running with elevated privileges
 pub fn is_elevated() -> bool {
@@ -79,10 +80,10 @@ pub fn run_as_admin() -> io::Result<()> {
 /// Example usage: Check elevation and prompt for UAC if needed
 pub fn ensure_admin_privileges() -> io::Result<()> {
     if !is_elevated() {
-        println!("Administrative privileges required. Requesting elevation...");
+        say!("Administrative privileges required. Requesting elevation...");
         run_as_admin()?;
     }
-    println!("Running with administrative privileges.");
+    say!("Running with administrative privileges.");
     Ok(())
 }
 
@@ -94,6 +95,6 @@ mod tests {
     fn test_elevation_check() {
         // This will return the actual elevation status
         let elevated = is_elevated();
-        println!("Currently elevated: {}", elevated);
+        say!("Currently elevated: {}", elevated);
     }
 }
\ No newline at end of file